@@ -0,0 +1,38 @@
+//! Compares round trips through a `SmartSlot` against a boxing `AtomicSlot`.
+//!
+//! Run with `cargo bench --bench smart_slot`.
+
+use atomic_slot::{AtomicSlot, SmartSlot};
+use std::hint::black_box;
+use std::time::Instant;
+
+const ITERATIONS: u32 = 10_000_000;
+
+fn bench(name: &str, mut f: impl FnMut(u32)) {
+    let start = Instant::now();
+    for i in 0..ITERATIONS {
+        f(black_box(i));
+    }
+    let per_op = start.elapsed() / ITERATIONS;
+    println!("{name:<24} {per_op:?}/op");
+}
+
+fn main() {
+    let smart = SmartSlot::empty();
+    bench("SmartSlot<u32> (inline)", |i| {
+        smart.store(Some(i));
+        black_box(smart.take());
+    });
+
+    let boxed = AtomicSlot::empty();
+    bench("AtomicSlot<u32>", |i| {
+        boxed.store(Some(Box::new(i)));
+        black_box(boxed.take());
+    });
+
+    let large = SmartSlot::empty();
+    bench("SmartSlot<[u32; 8]>", |i| {
+        large.store(Some([i; 8]));
+        black_box(large.take());
+    });
+}
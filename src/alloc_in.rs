@@ -0,0 +1,168 @@
+//! A slot for boxes allocated by a custom allocator.
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+
+use core::alloc::Allocator;
+use core::marker::PhantomData;
+use core::ptr;
+
+use crate::sync::{AtomicPtr, Ordering};
+
+/// A slot like [`AtomicSlot`](crate::AtomicSlot) for boxes allocated by the slot's own
+/// allocator `A`.
+///
+/// The slot owns a single `A`, which does not need to be `Clone`. Boxes are allocated
+/// through [`allocator`](AtomicSlotIn::allocator), so they borrow it as `Box<T, &A>`, and
+/// every box taken out of the slot is rebuilt against the same allocator, so its memory is
+/// always returned to `A`. Storing a box allocated by a different `A` instance panics.
+///
+/// Requires a nightly compiler and the `allocator_api` feature.
+///
+/// ```
+/// #![feature(allocator_api)]
+/// # use atomic_slot::AtomicSlotIn;
+/// use std::alloc::Global;
+///
+/// let slot = AtomicSlotIn::new_in(Global);
+/// slot.store(Some(Box::new_in(1, slot.allocator())));
+/// assert_eq!(*slot.take().unwrap(), 1);
+/// ```
+pub struct AtomicSlotIn<T, A: Allocator> {
+    inner: AtomicPtr<T>,
+    alloc: A,
+    _phantom: PhantomData<Option<Box<T, A>>>,
+}
+
+// Safety: values are only moved between threads, and the allocator is shared by reference
+unsafe impl<T: Send, A: Allocator + Send> Send for AtomicSlotIn<T, A> {}
+unsafe impl<T: Send, A: Allocator + Sync> Sync for AtomicSlotIn<T, A> {}
+
+impl<T, A: Allocator> AtomicSlotIn<T, A> {
+    /// Creates an empty `AtomicSlotIn` that owns `alloc`.
+    pub fn new_in(alloc: A) -> Self {
+        Self {
+            inner: AtomicPtr::new(ptr::null_mut()),
+            alloc,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns the allocator that boxes stored into this slot must be allocated with.
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    /// Atomically swaps out the current contents for `value`, returning the old contents.
+    ///
+    /// Uses acquire–release ordering.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` was not allocated through [`allocator`](AtomicSlotIn::allocator).
+    pub fn swap<'a>(&'a self, value: Option<Box<T, &'a A>>) -> Option<Box<T, &'a A>> {
+        let raw = value.map_or(ptr::null_mut(), |value| {
+            let (raw, alloc) = Box::into_raw_with_allocator(value);
+            assert!(
+                ptr::eq(alloc, &self.alloc),
+                "box was allocated by another allocator"
+            );
+            raw
+        });
+        let prev = self.inner.swap(raw, Ordering::AcqRel);
+        // Safety: every pointer in the slot was allocated by `self.alloc`
+        unsafe { self.box_from_raw(prev) }
+    }
+
+    /// Takes the current contents, leaving the slot empty.
+    ///
+    /// Uses acquire–release ordering.
+    pub fn take(&self) -> Option<Box<T, &A>> {
+        self.swap(None)
+    }
+
+    /// Stores `value` into the slot, dropping whatever was there before.
+    ///
+    /// Uses acquire–release ordering.
+    pub fn store<'a>(&'a self, value: Option<Box<T, &'a A>>) {
+        let _ = self.swap(value);
+    }
+
+    /// Returns `true` if the slot currently contains a value. Uses acquire ordering.
+    pub fn is_some(&self) -> bool {
+        !self.is_none()
+    }
+
+    /// Returns `true` if the slot is empty. Uses acquire ordering.
+    pub fn is_none(&self) -> bool {
+        self.inner.load(Ordering::Acquire).is_null()
+    }
+
+    /// # Safety
+    ///
+    /// `ptr` must be null or allocated by `self.alloc` and not be owned elsewhere.
+    unsafe fn box_from_raw(&self, ptr: *mut T) -> Option<Box<T, &A>> {
+        // Safety: guaranteed by the caller
+        (!ptr.is_null()).then(|| unsafe { Box::from_raw_in(ptr, &self.alloc) })
+    }
+}
+
+impl<T, A: Allocator> Drop for AtomicSlotIn<T, A> {
+    fn drop(&mut self) {
+        let ptr = self.inner.swap(ptr::null_mut(), Ordering::Relaxed);
+        // Safety: every pointer in the slot was allocated by `self.alloc`
+        drop(unsafe { self.box_from_raw(ptr) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::alloc::{AllocError, Layout};
+    use core::ptr::NonNull;
+    use std::alloc::Global;
+    use std::sync::atomic::AtomicUsize;
+
+    #[derive(Default)]
+    struct Tracking {
+        allocs: AtomicUsize,
+        frees: AtomicUsize,
+    }
+
+    unsafe impl Allocator for Tracking {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            self.allocs.fetch_add(1, Ordering::Relaxed);
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            self.frees.fetch_add(1, Ordering::Relaxed);
+            // Safety: forwarded from the caller, and every block came from `Global`
+            unsafe { Global.deallocate(ptr, layout) }
+        }
+    }
+
+    #[test]
+    fn frees_route_through_the_allocator() {
+        let slot = AtomicSlotIn::new_in(Tracking::default());
+        let alloc = slot.allocator();
+        slot.store(Some(Box::new_in(1u64, alloc)));
+        let old = slot.swap(Some(Box::new_in(2, alloc))).unwrap();
+        assert_eq!(*old, 1);
+        drop(old);
+        assert_eq!(alloc.allocs.load(Ordering::Relaxed), 2);
+        assert_eq!(alloc.frees.load(Ordering::Relaxed), 1);
+
+        slot.store(Some(Box::new_in(3, alloc)));
+        assert_eq!(alloc.frees.load(Ordering::Relaxed), 2);
+        assert!(slot.is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "another allocator")]
+    fn foreign_box_panics() {
+        let slot = AtomicSlotIn::new_in(Tracking::default());
+        let other = Tracking::default();
+        slot.store(Some(Box::new_in(1, &other)));
+    }
+}
@@ -0,0 +1,184 @@
+//! A slot sharing an `Arc` with many readers.
+
+#[cfg(not(feature = "std"))]
+use alloc::sync::Arc;
+#[cfg(feature = "std")]
+use std::sync::Arc;
+
+use core::marker::PhantomData;
+use core::ptr;
+
+use crate::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+use crate::sync::spin_loop;
+use crate::sync::with_ptr_mut;
+
+/// A slot holding an `Arc<T>` that readers can [`load`](AtomicArcSlot::load) without taking
+/// it out.
+///
+/// Loading clones the `Arc`, which means bumping the reference count of a value a concurrent
+/// swap may be about to release. The slot guards this with a reader count, like
+/// [`RwSpinSlot`](crate::RwSpinSlot): a load registers itself before reading the pointer,
+/// and a swap or take that removed an `Arc` waits until no load is registered before handing
+/// it back. Every reference count bump therefore happens while the slot still owns its
+/// reference, so the count never drops to zero under a reader.
+///
+/// Loads never wait. Writers spin while loads are in progress, so a steady stream of loads
+/// can delay them; loads only hold the count for a pointer read and an increment.
+pub struct AtomicArcSlot<T> {
+    ptr: AtomicPtr<T>,
+    readers: AtomicUsize,
+    _phantom: PhantomData<Option<Arc<T>>>,
+}
+
+// Safety: the slot shares its `Arc` with every thread that loads it
+unsafe impl<T: Send + Sync> Send for AtomicArcSlot<T> {}
+unsafe impl<T: Send + Sync> Sync for AtomicArcSlot<T> {}
+
+impl<T> Default for AtomicArcSlot<T> {
+    /// Creates an empty `AtomicArcSlot<T>`.
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<T> AtomicArcSlot<T> {
+    /// Creates a new `AtomicArcSlot` containing `value`.
+    pub fn new(value: Arc<T>) -> Self {
+        Self::with_ptr(Arc::into_raw(value).cast_mut())
+    }
+
+    /// Creates an empty `AtomicArcSlot`.
+    pub fn empty() -> Self {
+        Self::with_ptr(ptr::null_mut())
+    }
+
+    fn with_ptr(ptr: *mut T) -> Self {
+        Self {
+            ptr: AtomicPtr::new(ptr),
+            readers: AtomicUsize::new(0),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns a clone of the current `Arc`, leaving it in the slot.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicArcSlot;
+    /// use std::sync::Arc;
+    ///
+    /// let slot = AtomicArcSlot::new(Arc::new("config"));
+    /// let a = slot.load().unwrap();
+    /// let b = slot.load().unwrap();
+    /// assert!(Arc::ptr_eq(&a, &b));
+    /// ```
+    pub fn load(&self) -> Option<Arc<T>> {
+        self.readers.fetch_add(1, Ordering::SeqCst);
+        let ptr = self.ptr.load(Ordering::SeqCst);
+        let value = (!ptr.is_null()).then(|| {
+            // Safety: a writer that removed `ptr` waits for this reader before releasing the
+            // slot's reference, so the count is at least one here
+            unsafe {
+                Arc::increment_strong_count(ptr);
+                Arc::from_raw(ptr)
+            }
+        });
+        self.readers.fetch_sub(1, Ordering::Release);
+        value
+    }
+
+    /// Atomically swaps out the current contents for `value`, returning the old contents.
+    ///
+    /// Waits for in-progress loads before returning.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicArcSlot;
+    /// use std::sync::Arc;
+    ///
+    /// let slot = AtomicArcSlot::new(Arc::new(1));
+    /// let old = slot.swap(Some(Arc::new(2)));
+    /// assert_eq!(*old.unwrap(), 1);
+    /// assert_eq!(*slot.load().unwrap(), 2);
+    /// ```
+    pub fn swap(&self, value: Option<Arc<T>>) -> Option<Arc<T>> {
+        let raw = value.map_or(ptr::null_mut(), |value| Arc::into_raw(value).cast_mut());
+        let prev = self.ptr.swap(raw, Ordering::SeqCst);
+        if prev.is_null() {
+            return None;
+        }
+        while self.readers.load(Ordering::SeqCst) != 0 {
+            spin_loop();
+        }
+        // Safety: `prev` came from `Arc::into_raw` and its reference was owned by the slot
+        Some(unsafe { Arc::from_raw(prev) })
+    }
+
+    /// Takes the current contents, leaving the slot empty.
+    ///
+    /// Waits for in-progress loads before returning.
+    pub fn take(&self) -> Option<Arc<T>> {
+        self.swap(None)
+    }
+
+    /// Stores `value` into the slot, releasing whatever was there before.
+    ///
+    /// Waits for in-progress loads before returning.
+    pub fn store(&self, value: Option<Arc<T>>) {
+        let _ = self.swap(value);
+    }
+
+    /// Returns `true` if the slot currently contains a value. Uses acquire ordering.
+    pub fn is_some(&self) -> bool {
+        !self.ptr.load(Ordering::Acquire).is_null()
+    }
+}
+
+impl<T> Drop for AtomicArcSlot<T> {
+    fn drop(&mut self) {
+        let ptr = with_ptr_mut(&mut self.ptr, |ptr| *ptr);
+        if !ptr.is_null() {
+            // Safety: `ptr` came from `Arc::into_raw` and the slot is being destroyed
+            drop(unsafe { Arc::from_raw(ptr) });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+    use std::thread;
+
+    #[test]
+    fn readers_load_while_writer_swaps() {
+        let slot = Arc::new(AtomicArcSlot::new(Arc::new(0usize)));
+        let done = Arc::new(AtomicBool::new(false));
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let slot = slot.clone();
+                let done = done.clone();
+                thread::spawn(move || {
+                    let mut last = 0;
+                    while !done.load(Ordering::Acquire) {
+                        let value = *slot.load().unwrap();
+                        assert!(value >= last);
+                        last = value;
+                    }
+                })
+            })
+            .collect();
+
+        for i in 1..=1000 {
+            let old = slot.swap(Some(Arc::new(i))).unwrap();
+            assert_eq!(*old, i - 1);
+        }
+        done.store(true, Ordering::Release);
+        for reader in readers {
+            reader.join().unwrap();
+        }
+
+        let last = slot.take().unwrap();
+        assert_eq!(*last, 1000);
+        assert_eq!(Arc::strong_count(&last), 1);
+        assert!(slot.load().is_none());
+    }
+}
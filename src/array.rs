@@ -0,0 +1,170 @@
+//! A fixed-size array of slots.
+
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, vec::Vec};
+
+use crate::sync::Ordering;
+use crate::{AtomicSlot, occupied_count, take_all};
+
+/// `N` independent [`AtomicSlot`]s addressed by index, such as a bank of per-worker
+/// mailboxes.
+///
+/// Each slot is operated on atomically on its own; the operations spanning the whole array,
+/// [`drain`](Self::drain) and [`occupied_count`](Self::occupied_count), visit the slots one
+/// after another and are not atomic snapshots. Dropping the array drops every value still in
+/// it.
+///
+/// ```
+/// # use atomic_slot::AtomicSlotArray;
+/// let mailboxes = AtomicSlotArray::<&str, 4>::empty();
+/// mailboxes.store(2, Some(Box::new("job")));
+/// assert_eq!(mailboxes.occupied_count(), 1);
+/// assert_eq!(*mailboxes.take(2).unwrap(), "job");
+/// ```
+pub struct AtomicSlotArray<T, const N: usize> {
+    slots: [AtomicSlot<T>; N],
+}
+
+impl<T, const N: usize> Default for AtomicSlotArray<T, N> {
+    /// Creates an `AtomicSlotArray` of empty slots.
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<T, const N: usize> From<[AtomicSlot<T>; N]> for AtomicSlotArray<T, N> {
+    fn from(slots: [AtomicSlot<T>; N]) -> Self {
+        Self { slots }
+    }
+}
+
+impl<T, const N: usize> AtomicSlotArray<T, N> {
+    /// Creates an `AtomicSlotArray` of empty slots.
+    #[cfg(not(loom))]
+    pub const fn empty() -> Self {
+        Self {
+            slots: [const { AtomicSlot::empty() }; N],
+        }
+    }
+
+    /// Creates an `AtomicSlotArray` of empty slots.
+    #[cfg(loom)]
+    pub fn empty() -> Self {
+        Self {
+            slots: core::array::from_fn(|_| AtomicSlot::empty()),
+        }
+    }
+
+    /// Atomically swaps out the contents of slot `index` for `value`, returning the old
+    /// contents.
+    ///
+    /// Uses acquire–release ordering.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not below `N`.
+    pub fn swap(&self, index: usize, value: Option<Box<T>>) -> Option<Box<T>> {
+        self.slots[index].swap(value)
+    }
+
+    /// Takes the contents of slot `index`, leaving it empty.
+    ///
+    /// Uses acquire–release ordering.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not below `N`.
+    pub fn take(&self, index: usize) -> Option<Box<T>> {
+        self.slots[index].take()
+    }
+
+    /// Stores `value` into slot `index`, dropping whatever was there before.
+    ///
+    /// Uses acquire–release ordering.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not below `N`.
+    pub fn store(&self, index: usize, value: Option<Box<T>>) {
+        self.slots[index].store(value);
+    }
+
+    /// Takes every value out of the array, in index order, leaving every slot empty.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlotArray;
+    /// let slots = AtomicSlotArray::<u32, 3>::empty();
+    /// slots.store(0, Some(Box::new(1)));
+    /// slots.store(2, Some(Box::new(3)));
+    /// let values: Vec<_> = slots.drain().into_iter().map(|b| *b).collect();
+    /// assert_eq!(values, [1, 3]);
+    /// assert_eq!(slots.occupied_count(), 0);
+    /// ```
+    pub fn drain(&self) -> Vec<Box<T>> {
+        take_all(&self.slots)
+    }
+
+    /// Returns how many slots are occupied. Loads each slot with acquire ordering.
+    pub fn occupied_count(&self) -> usize {
+        occupied_count(&self.slots, Ordering::Acquire)
+    }
+
+    /// Returns the slots, for the operations not mirrored on the array.
+    pub fn as_slots(&self) -> &[AtomicSlot<T>; N] {
+        &self.slots
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn indexed_access() {
+        let slots = AtomicSlotArray::<usize, 4>::default();
+        for i in 0..4 {
+            assert!(slots.swap(i, Some(Box::new(i))).is_none());
+        }
+        assert_eq!(*slots.swap(1, Some(Box::new(10))).unwrap(), 1);
+        assert_eq!(*slots.take(3).unwrap(), 3);
+        assert!(slots.take(3).is_none());
+        slots.store(0, None);
+        assert_eq!(slots.occupied_count(), 2);
+        assert!(slots.as_slots()[1].is_some());
+    }
+
+    #[test]
+    fn send_and_sync_follow_t_send() {
+        fn assert_send_sync<S: Send + Sync>() {}
+        assert_send_sync::<AtomicSlotArray<std::cell::Cell<u8>, 3>>();
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_out_of_bounds_panics() {
+        let _ = AtomicSlotArray::<u8, 2>::empty().take(2);
+    }
+
+    #[test]
+    fn drain_takes_everything() {
+        let slots: AtomicSlotArray<u32, 5> =
+            core::array::from_fn(|i| AtomicSlot::new(Box::new(i as u32))).into();
+        let values: Vec<_> = slots.drain().into_iter().map(|b| *b).collect();
+        assert_eq!(values, [0, 1, 2, 3, 4]);
+        assert_eq!(slots.occupied_count(), 0);
+        assert!(slots.drain().is_empty());
+    }
+
+    #[test]
+    fn drop_frees_every_value() {
+        let tracker = Arc::new(());
+        let slots = AtomicSlotArray::<Arc<()>, 8>::empty();
+        for i in (0..8).step_by(2) {
+            slots.store(i, Some(Box::new(tracker.clone())));
+        }
+        assert_eq!(Arc::strong_count(&tracker), 5);
+        drop(slots);
+        assert_eq!(Arc::strong_count(&tracker), 1);
+    }
+}
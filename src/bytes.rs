@@ -0,0 +1,229 @@
+//! A slot that exchanges small plain-old-data values as canonical little-endian bytes.
+
+use core::marker::PhantomData;
+
+use bytemuck::Pod;
+
+use crate::sync::atomic::{AtomicU64, Ordering, fence};
+use crate::sync::spin_loop;
+
+/// Plain-old-data types that can be converted to and from little-endian byte order.
+///
+/// Converting is its own inverse: on little-endian targets it does nothing, and on big-endian
+/// targets it reverses the bytes of every field. Implement it for a `#[repr(C)]` struct by
+/// converting each field.
+pub trait ByteOrder: Pod {
+    /// Converts `self` between native and little-endian byte order.
+    fn to_le(self) -> Self;
+}
+
+macro_rules! impl_byte_order {
+    ($($t:ty),*) => {
+        $(
+            impl ByteOrder for $t {
+                fn to_le(self) -> Self {
+                    <$t>::to_le(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_byte_order!(
+    u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize
+);
+
+impl ByteOrder for f32 {
+    fn to_le(self) -> Self {
+        f32::from_bits(self.to_bits().to_le())
+    }
+}
+
+impl ByteOrder for f64 {
+    fn to_le(self) -> Self {
+        f64::from_bits(self.to_bits().to_le())
+    }
+}
+
+impl<T: ByteOrder, const N: usize> ByteOrder for [T; N] {
+    fn to_le(self) -> Self {
+        self.map(T::to_le)
+    }
+}
+
+const WORDS: usize = 4;
+
+/// A slot holding a small [`ByteOrder`] value inline, meant to live in memory shared between
+/// processes that may differ in endianness.
+///
+/// The value is stored as its little-endian bytes in a fixed region of
+/// [`ByteSlot::CAPACITY`] bytes, so `size_of::<T>()` may be at most that; this is checked at
+/// compile time. The sequence counter guarding the region is little-endian as well, so every
+/// process agrees on the layout.
+///
+/// Writers and readers coordinate through a seqlock: the counter is odd while a write is in
+/// progress, and a reader retries if the counter changed while it copied the bytes. Readers
+/// therefore never observe a torn value, and they never block a writer. Concurrent writers
+/// are safe but serialize.
+#[repr(C)]
+pub struct ByteSlot<T: ByteOrder> {
+    seq: AtomicU64,
+    words: [AtomicU64; WORDS],
+    _phantom: PhantomData<T>,
+}
+
+impl<T: ByteOrder> Default for ByteSlot<T> {
+    /// Creates a `ByteSlot<T>` holding the all-zero value.
+    fn default() -> Self {
+        Self::new(&T::zeroed())
+    }
+}
+
+impl<T: ByteOrder> ByteSlot<T> {
+    /// The largest value size, in bytes, a `ByteSlot` can hold.
+    pub const CAPACITY: usize = WORDS * size_of::<u64>();
+
+    const FITS: () = assert!(
+        size_of::<T>() <= Self::CAPACITY,
+        "ByteSlot<T> requires size_of::<T>() <= ByteSlot::CAPACITY"
+    );
+
+    /// Creates a new `ByteSlot` holding `value`.
+    pub fn new(value: &T) -> Self {
+        let _: () = Self::FITS;
+        let words = encode(value).map(AtomicU64::new);
+        Self {
+            seq: AtomicU64::new(0),
+            words,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Replaces the value, writing it in little-endian byte order.
+    ///
+    /// ```
+    /// # use atomic_slot::ByteSlot;
+    /// let slot = ByteSlot::new(&1u32);
+    /// slot.store(&2);
+    /// assert_eq!(slot.load(), 2);
+    /// ```
+    pub fn store(&self, value: &T) {
+        let _: () = Self::FITS;
+        let mut raw = self.seq.load(Ordering::Relaxed);
+        let seq = loop {
+            let seq = u64::from_le(raw);
+            if seq & 1 == 1 {
+                spin_loop();
+                raw = self.seq.load(Ordering::Relaxed);
+                continue;
+            }
+            match self.seq.compare_exchange_weak(
+                raw,
+                seq.wrapping_add(1).to_le(),
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break seq,
+                Err(actual) => raw = actual,
+            }
+        };
+        // Keeps the data stores below from becoming visible before the odd counter
+        fence(Ordering::Release);
+        for (word, bits) in self.words.iter().zip(encode(value)) {
+            word.store(bits, Ordering::Relaxed);
+        }
+        self.seq
+            .store(seq.wrapping_add(2).to_le(), Ordering::Release);
+    }
+
+    /// Returns a copy of the value, converted to native byte order.
+    ///
+    /// Retries while a write is in progress.
+    pub fn load(&self) -> T {
+        let _: () = Self::FITS;
+        loop {
+            let before = self.seq.load(Ordering::Acquire);
+            if u64::from_le(before) & 1 == 1 {
+                spin_loop();
+                continue;
+            }
+            let words = core::array::from_fn(|i| self.words[i].load(Ordering::Relaxed));
+            // Keeps the data loads above from being satisfied after the counter check
+            fence(Ordering::Acquire);
+            if self.seq.load(Ordering::Relaxed) == before {
+                return decode(words);
+            }
+        }
+    }
+}
+
+/// Packs the little-endian bytes of `value` into words whose in-memory bytes are the same on
+/// every target.
+fn encode<T: ByteOrder>(value: &T) -> [u64; WORDS] {
+    let mut bytes = [0u8; WORDS * size_of::<u64>()];
+    bytes[..size_of::<T>()].copy_from_slice(bytemuck::bytes_of(&value.to_le()));
+    core::array::from_fn(|i| {
+        let chunk = &bytes[i * size_of::<u64>()..][..size_of::<u64>()];
+        u64::from_ne_bytes(chunk.try_into().unwrap())
+    })
+}
+
+fn decode<T: ByteOrder>(words: [u64; WORDS]) -> T {
+    let mut bytes = [0u8; WORDS * size_of::<u64>()];
+    for (chunk, word) in bytes.chunks_exact_mut(size_of::<u64>()).zip(words) {
+        chunk.copy_from_slice(&word.to_ne_bytes());
+    }
+    bytemuck::pod_read_unaligned::<T>(&bytes[..size_of::<T>()]).to_le()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    #[repr(C)]
+    struct Frame {
+        id: u32,
+        flags: u16,
+        kind: u16,
+        scale: f64,
+        samples: [i16; 4],
+    }
+
+    // Safety: `Frame` is `repr(C)` with no padding and only plain integer and float fields
+    unsafe impl bytemuck::Zeroable for Frame {}
+    unsafe impl Pod for Frame {}
+
+    impl ByteOrder for Frame {
+        fn to_le(self) -> Self {
+            Frame {
+                id: self.id.to_le(),
+                flags: self.flags.to_le(),
+                kind: self.kind.to_le(),
+                scale: ByteOrder::to_le(self.scale),
+                samples: ByteOrder::to_le(self.samples),
+            }
+        }
+    }
+
+    #[test]
+    fn round_trips_multi_field_struct() {
+        let frame = Frame {
+            id: 0x0102_0304,
+            flags: 0xabcd,
+            kind: 7,
+            scale: 1.5,
+            samples: [-1, 2, -3, 4],
+        };
+        let slot = ByteSlot::<Frame>::default();
+        assert_eq!(slot.load(), bytemuck::Zeroable::zeroed());
+
+        slot.store(&frame);
+        assert_eq!(slot.load(), frame);
+
+        // The shared bytes are little-endian regardless of the target
+        let first = slot.words[0].load(Ordering::Relaxed).to_ne_bytes();
+        assert_eq!(first[..4], 0x0102_0304u32.to_le_bytes());
+        assert_eq!(u64::from_le(slot.seq.load(Ordering::Relaxed)), 2);
+    }
+}
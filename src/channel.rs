@@ -0,0 +1,247 @@
+//! A bounded channel of capacity one, backed by an [`AtomicSlot`].
+
+use std::sync::mpsc::{RecvError, SendError, TryRecvError, TrySendError};
+use std::sync::{Arc, Condvar, Mutex};
+
+use crate::AtomicSlot;
+use crate::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Creates a channel that holds at most one message at a time.
+///
+/// The message is stored in an [`AtomicSlot`], so [`try_send`](SlotSender::try_send) and
+/// [`try_recv`](SlotReceiver::try_recv) are a single atomic operation. The blocking
+/// [`send`](SlotSender::send) waits until the slot is empty, and [`recv`](SlotReceiver::recv)
+/// until it is full.
+///
+/// The sender can be cloned; the channel disconnects once the receiver or every sender is
+/// dropped. A message sent before the last sender was dropped can still be received, after
+/// which `recv` reports disconnection. The errors are those of [`std::sync::mpsc`].
+///
+/// ```
+/// # use atomic_slot::slot_channel;
+/// let (tx, rx) = slot_channel();
+/// let producer = std::thread::spawn(move || {
+///     for i in 0..3 {
+///         tx.send(i).unwrap();
+///     }
+/// });
+/// assert_eq!(rx.iter().collect::<Vec<_>>(), [0, 1, 2]);
+/// producer.join().unwrap();
+/// ```
+pub fn slot_channel<T>() -> (SlotSender<T>, SlotReceiver<T>) {
+    let shared = Arc::new(Shared {
+        slot: AtomicSlot::empty(),
+        lock: Mutex::new(()),
+        changed: Condvar::new(),
+        senders: AtomicUsize::new(1),
+        receiver_alive: AtomicBool::new(true),
+    });
+    (
+        SlotSender {
+            shared: shared.clone(),
+        },
+        SlotReceiver { shared },
+    )
+}
+
+struct Shared<T> {
+    slot: AtomicSlot<T>,
+    lock: Mutex<()>,
+    changed: Condvar,
+    senders: AtomicUsize,
+    receiver_alive: AtomicBool,
+}
+
+impl<T> Shared<T> {
+    /// Wakes every waiting thread after the state was changed.
+    ///
+    /// Taking the lock ensures a waiter cannot miss the change between checking the state and
+    /// starting to wait.
+    fn notify(&self) {
+        drop(self.lock.lock().unwrap_or_else(|e| e.into_inner()));
+        self.changed.notify_all();
+    }
+
+    /// Blocks until `ready` returns `true`, re-checking whenever the state changes.
+    fn wait_until(&self, mut ready: impl FnMut() -> bool) {
+        let mut guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        while !ready() {
+            guard = self.changed.wait(guard).unwrap_or_else(|e| e.into_inner());
+        }
+    }
+}
+
+/// The sending half of a [`slot_channel`].
+pub struct SlotSender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> SlotSender<T> {
+    /// Sends `value`, blocking while a previous message has not been received yet.
+    ///
+    /// Fails, handing `value` back, if the receiver has been dropped.
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        let mut value = Box::new(value);
+        loop {
+            match self.try_send_boxed(value) {
+                Ok(()) => return Ok(()),
+                Err(TrySendError::Disconnected(value)) => return Err(SendError(*value)),
+                Err(TrySendError::Full(rejected)) => value = rejected,
+            }
+            let shared = &*self.shared;
+            shared.wait_until(|| {
+                shared.slot.is_none() || !shared.receiver_alive.load(Ordering::Acquire)
+            });
+        }
+    }
+
+    /// Sends `value` if the channel is empty, without blocking.
+    pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        self.try_send_boxed(Box::new(value)).map_err(|e| match e {
+            TrySendError::Full(value) => TrySendError::Full(*value),
+            TrySendError::Disconnected(value) => TrySendError::Disconnected(*value),
+        })
+    }
+
+    fn try_send_boxed(&self, value: Box<T>) -> Result<(), TrySendError<Box<T>>> {
+        if !self.shared.receiver_alive.load(Ordering::Acquire) {
+            return Err(TrySendError::Disconnected(value));
+        }
+        self.shared
+            .slot
+            .try_store(value)
+            .map_err(TrySendError::Full)?;
+        self.shared.notify();
+        Ok(())
+    }
+}
+
+impl<T> Clone for SlotSender<T> {
+    fn clone(&self) -> Self {
+        self.shared.senders.fetch_add(1, Ordering::Relaxed);
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Drop for SlotSender<T> {
+    fn drop(&mut self) {
+        if self.shared.senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.shared.notify();
+        }
+    }
+}
+
+/// The receiving half of a [`slot_channel`].
+pub struct SlotReceiver<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> SlotReceiver<T> {
+    /// Receives a message, blocking until one is available.
+    ///
+    /// Fails once the channel is empty and every sender has been dropped.
+    pub fn recv(&self) -> Result<T, RecvError> {
+        loop {
+            match self.try_recv() {
+                Ok(value) => return Ok(value),
+                Err(TryRecvError::Disconnected) => return Err(RecvError),
+                Err(TryRecvError::Empty) => {}
+            }
+            let shared = &*self.shared;
+            shared.wait_until(|| {
+                shared.slot.is_some() || shared.senders.load(Ordering::Acquire) == 0
+            });
+        }
+    }
+
+    /// Receives a message if one is available, without blocking.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        if let Some(value) = self.shared.slot.take() {
+            self.shared.notify();
+            return Ok(*value);
+        }
+        if self.shared.senders.load(Ordering::Acquire) == 0 {
+            // A last message may have been sent just before the final sender was dropped
+            return self
+                .shared
+                .slot
+                .take()
+                .map(|value| *value)
+                .ok_or(TryRecvError::Disconnected);
+        }
+        Err(TryRecvError::Empty)
+    }
+
+    /// Returns an iterator that receives messages until the channel is disconnected.
+    pub fn iter(&self) -> impl Iterator<Item = T> + '_ {
+        core::iter::from_fn(|| self.recv().ok())
+    }
+}
+
+impl<T> Drop for SlotReceiver<T> {
+    fn drop(&mut self) {
+        self.shared.receiver_alive.store(false, Ordering::Release);
+        self.shared.notify();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn try_send_and_try_recv() {
+        let (tx, rx) = slot_channel();
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+        tx.try_send(1).unwrap();
+        assert_eq!(tx.try_send(2), Err(TrySendError::Full(2)));
+        assert_eq!(rx.try_recv(), Ok(1));
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+    }
+
+    #[test]
+    fn send_blocks_until_received() {
+        let (tx, rx) = slot_channel();
+        let producer = thread::spawn(move || {
+            for i in 0..100 {
+                tx.send(i).unwrap();
+            }
+        });
+        let received: Vec<_> = rx.iter().collect();
+        assert_eq!(received, (0..100).collect::<Vec<_>>());
+        producer.join().unwrap();
+    }
+
+    #[test]
+    fn sender_drop_disconnects_after_last_message() {
+        let (tx, rx) = slot_channel();
+        let tx2 = tx.clone();
+        tx.send(String::from("last")).unwrap();
+        drop(tx);
+        assert_eq!(rx.try_recv(), Ok(String::from("last")));
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+        drop(tx2);
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+        assert_eq!(rx.recv(), Err(RecvError));
+    }
+
+    #[test]
+    fn receiver_drop_disconnects_blocked_sender() {
+        let (tx, rx) = slot_channel();
+        tx.send(1).unwrap();
+        let producer = thread::spawn(move || tx.send(2));
+        drop(rx);
+        assert_eq!(producer.join().unwrap(), Err(SendError(2)));
+    }
+
+    #[test]
+    fn blocked_receiver_wakes_on_sender_drop() {
+        let (tx, rx) = slot_channel::<i32>();
+        let consumer = thread::spawn(move || rx.recv());
+        drop(tx);
+        assert_eq!(consumer.join().unwrap(), Err(RecvError));
+    }
+}
@@ -0,0 +1,110 @@
+//! Dropping values away from the hot path.
+
+use std::sync::mpsc::{self, Sender};
+use std::thread::{self, JoinHandle};
+
+use crate::AtomicSlot;
+
+/// A background thread that drops the boxes sent to it.
+///
+/// Useful when a value's `Drop` is expensive, such as a large nested structure, and the
+/// thread that takes it out of a slot is latency sensitive. Handing the box over costs a
+/// channel send; the destructor and deallocation run on the dropper thread instead.
+///
+/// Dropping the `DeferredDropper` waits for every box already sent to be dropped.
+pub struct DeferredDropper<T: Send + 'static> {
+    sender: Option<Sender<Box<T>>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<T: Send + 'static> Default for DeferredDropper<T> {
+    /// Spawns a new dropper thread.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Send + 'static> DeferredDropper<T> {
+    /// Spawns a new dropper thread.
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel::<Box<T>>();
+        let thread = thread::Builder::new()
+            .name("atomic-slot-dropper".into())
+            .spawn(move || receiver.into_iter().for_each(drop))
+            .expect("failed to spawn dropper thread");
+        Self {
+            sender: Some(sender),
+            thread: Some(thread),
+        }
+    }
+
+    /// Sends `value` to the dropper thread to be dropped there.
+    pub fn drop_later(&self, value: Box<T>) {
+        let sender = self.sender.as_ref().expect("sender lives until drop");
+        // The receiver only goes away if the dropper thread panicked; drop in place then
+        if let Err(mpsc::SendError(value)) = sender.send(value) {
+            drop(value);
+        }
+    }
+}
+
+impl<T: Send + 'static> Drop for DeferredDropper<T> {
+    fn drop(&mut self) {
+        drop(self.sender.take());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl<T: Send + 'static> AtomicSlot<T> {
+    /// Takes the current contents and hands them to `dropper`, returning whether the slot
+    /// held a value.
+    ///
+    /// ```
+    /// # use atomic_slot::{AtomicSlot, DeferredDropper};
+    /// let dropper = DeferredDropper::new();
+    /// let slot = AtomicSlot::new(Box::new(vec![0u8; 1 << 20]));
+    /// assert!(slot.take_defer_drop(&dropper));
+    /// assert!(!slot.take_defer_drop(&dropper));
+    /// ```
+    pub fn take_defer_drop(&self, dropper: &DeferredDropper<T>) -> bool {
+        match self.take() {
+            Some(value) => {
+                dropper.drop_later(value);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use std::thread::ThreadId;
+
+    struct RecordDrop(Arc<Mutex<Vec<ThreadId>>>);
+
+    impl Drop for RecordDrop {
+        fn drop(&mut self) {
+            self.0.lock().unwrap().push(thread::current().id());
+        }
+    }
+
+    #[test]
+    fn drops_on_background_thread() {
+        let drops = Arc::new(Mutex::new(Vec::new()));
+        let dropper = DeferredDropper::new();
+        let slot = AtomicSlot::new(Box::new(RecordDrop(drops.clone())));
+
+        assert!(slot.take_defer_drop(&dropper));
+        assert!(!slot.take_defer_drop(&dropper));
+        drop(dropper);
+
+        let drops = drops.lock().unwrap();
+        assert_eq!(drops.len(), 1);
+        assert_ne!(drops[0], thread::current().id());
+    }
+}
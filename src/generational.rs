@@ -0,0 +1,172 @@
+//! A slot handing out generation-stamped handles on every store.
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+
+use core::marker::PhantomData;
+use core::ptr;
+
+use crate::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+use crate::sync::with_ptr_mut;
+
+/// A slot that returns a [`Handle`] from every [`store`](GenSlot::store), and only lets
+/// [`take`](GenSlot::take) succeed with the handle of the value currently stored.
+///
+/// This prevents a consumer holding a handle to a previous occupant from accidentally taking
+/// a newer value.
+///
+/// The slot stamps the stored pointer with the low bits of a generation counter, using the
+/// bits that are always zero due to `T`'s alignment. A handle matches if both the address and
+/// the stamp match, so a stale handle can only succeed if the allocator reuses the same
+/// address for a newer value *and* the generation has wrapped around within the available
+/// bits. With `align_of::<T>() == 1` there are no spare bits and only the address is compared.
+pub struct GenSlot<T> {
+    inner: AtomicPtr<T>,
+    generation: AtomicUsize,
+    _phantom: PhantomData<Option<Box<T>>>,
+}
+
+/// Identifies one particular value stored in a [`GenSlot`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Handle {
+    stamp: usize,
+    generation: usize,
+}
+
+impl Handle {
+    /// Returns the generation of the store that produced this handle.
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+}
+
+// Safety: the word is an owned `Box<T>` pointer stamped with generation bits, and `store` and
+// `take` move whole boxes in and out while `Handle`s carry only the stamp and generation, never
+// a reference to the value, so a `T` is only moved between threads
+unsafe impl<T: Send> Send for GenSlot<T> {}
+unsafe impl<T: Send> Sync for GenSlot<T> {}
+
+impl<T> Default for GenSlot<T> {
+    /// Creates an empty `GenSlot<T>`.
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<T> GenSlot<T> {
+    const STAMP_MASK: usize = align_of::<T>() - 1;
+
+    loom_const_fn! {
+        /// Creates an empty `GenSlot`.
+        pub const fn empty() -> Self {
+            Self {
+                inner: AtomicPtr::new(ptr::null_mut()),
+                generation: AtomicUsize::new(0),
+                _phantom: PhantomData,
+            }
+        }
+    }
+
+    /// Stores `value`, dropping whatever was there before, and returns its handle.
+    ///
+    /// Uses acquire–release ordering.
+    ///
+    /// ```
+    /// # use atomic_slot::GenSlot;
+    /// let slot = GenSlot::empty();
+    /// let handle = slot.store(Box::new(1u64));
+    /// assert_eq!(*slot.take(handle).unwrap(), 1);
+    /// ```
+    pub fn store(&self, value: Box<T>) -> Handle {
+        let generation = self.generation.fetch_add(1, Ordering::Relaxed);
+        let stamped = Box::into_raw(value).map_addr(|addr| addr | (generation & Self::STAMP_MASK));
+        let prev = self.inner.swap(stamped, Ordering::AcqRel);
+        // Safety: non-null pointers in the slot are stamped `Box::into_raw` pointers
+        drop(unsafe { Self::from_stamped(prev) });
+        Handle {
+            stamp: stamped.addr(),
+            generation,
+        }
+    }
+
+    /// Takes the value identified by `handle`, leaving the slot empty.
+    ///
+    /// Returns `None`, leaving the slot untouched, if the slot is empty or holds a different
+    /// value. Uses acquire–release ordering.
+    ///
+    /// ```
+    /// # use atomic_slot::GenSlot;
+    /// let slot = GenSlot::empty();
+    /// let stale = slot.store(Box::new(1u64));
+    /// let current = slot.store(Box::new(2u64));
+    /// assert!(slot.take(stale).is_none());
+    /// assert_eq!(*slot.take(current).unwrap(), 2);
+    /// ```
+    pub fn take(&self, handle: Handle) -> Option<Box<T>> {
+        let prev = self
+            .inner
+            .compare_exchange(
+                ptr::without_provenance_mut(handle.stamp),
+                ptr::null_mut(),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            )
+            .ok()?;
+        // Safety: non-null pointers in the slot are stamped `Box::into_raw` pointers
+        unsafe { Self::from_stamped(prev) }
+    }
+
+    /// Takes whatever value is stored, regardless of its handle.
+    ///
+    /// Uses acquire–release ordering.
+    pub fn take_any(&self) -> Option<Box<T>> {
+        let prev = self.inner.swap(ptr::null_mut(), Ordering::AcqRel);
+        // Safety: non-null pointers in the slot are stamped `Box::into_raw` pointers
+        unsafe { Self::from_stamped(prev) }
+    }
+
+    /// Returns `true` if the slot is empty. Uses acquire ordering.
+    pub fn is_none(&self) -> bool {
+        self.inner.load(Ordering::Acquire).is_null()
+    }
+
+    /// # Safety
+    ///
+    /// `ptr` must be null or a stamped pointer from `Box::into_raw` not owned elsewhere.
+    unsafe fn from_stamped(ptr: *mut T) -> Option<Box<T>> {
+        if ptr.is_null() {
+            None
+        } else {
+            let ptr = ptr.map_addr(|addr| addr & !Self::STAMP_MASK);
+            // Safety: guaranteed by the caller
+            Some(unsafe { Box::from_raw(ptr) })
+        }
+    }
+}
+
+impl<T> Drop for GenSlot<T> {
+    fn drop(&mut self) {
+        // Safety: non-null pointers in the slot are stamped `Box::into_raw` pointers
+        drop(unsafe { Self::from_stamped(with_ptr_mut(&mut self.inner, |ptr| *ptr)) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_handle_cannot_take() {
+        let slot = GenSlot::empty();
+        let first = slot.store(Box::new(1u32));
+        let second = slot.store(Box::new(2u32));
+        assert_ne!(first, second);
+        assert_eq!(second.generation(), first.generation() + 1);
+
+        assert!(slot.take(first).is_none());
+        assert!(!slot.is_none());
+        assert_eq!(*slot.take(second).unwrap(), 2);
+        assert!(slot.take(second).is_none());
+        assert!(slot.is_none());
+    }
+}
@@ -0,0 +1,192 @@
+//! A slot that remembers whether it was ever filled.
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+
+use core::marker::PhantomData;
+use core::ptr;
+
+use crate::sync::atomic::{AtomicPtr, Ordering};
+use crate::sync::with_ptr_mut;
+
+/// Low pointer bit recording that a value has been stored at least once.
+const EVER_SET: usize = 1;
+
+/// A slot like [`AtomicSlot`](crate::AtomicSlot) that also records whether it was ever filled.
+///
+/// This distinguishes "never initialized" from "taken and now empty", for example to detect
+/// whether a one-shot producer ever ran.
+///
+/// The history bit is stored in the lowest bit of the pointer, so the slot is still a single
+/// word and every operation is a single atomic instruction or a load followed by one. This
+/// requires `align_of::<T>() >= 2`, which is checked at compile time.
+pub struct HistorySlot<T> {
+    inner: AtomicPtr<T>,
+    _phantom: PhantomData<Option<Box<T>>>,
+}
+
+// Safety: apart from the `EVER_SET` bit the word is an owned `Box<T>` pointer, and `swap`,
+// `take` and `store` move whole boxes in and out; `was_ever_set` only reads the bit, so no
+// `&T` is ever reachable through `&self`
+unsafe impl<T: Send> Send for HistorySlot<T> {}
+unsafe impl<T: Send> Sync for HistorySlot<T> {}
+
+impl<T> Default for HistorySlot<T> {
+    /// Creates an empty `HistorySlot<T>` that was never set.
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<T> HistorySlot<T> {
+    const TAGGABLE: () = assert!(
+        align_of::<T>() >= 2,
+        "HistorySlot<T> requires align_of::<T>() >= 2"
+    );
+
+    /// Creates a new `HistorySlot` containing `value`.
+    ///
+    /// ```
+    /// # use atomic_slot::HistorySlot;
+    /// let slot = HistorySlot::new(Box::new(1u32));
+    /// assert!(slot.was_ever_set());
+    /// ```
+    pub fn new(value: Box<T>) -> Self {
+        let _: () = Self::TAGGABLE;
+        Self {
+            inner: AtomicPtr::new(tag(Box::into_raw(value))),
+            _phantom: PhantomData,
+        }
+    }
+
+    loom_const_fn! {
+        /// Creates an empty `HistorySlot` that was never set.
+        ///
+        /// ```
+        /// # use atomic_slot::HistorySlot;
+        /// let slot: HistorySlot<u32> = HistorySlot::empty();
+        /// assert!(!slot.was_ever_set());
+        /// ```
+        pub const fn empty() -> Self {
+            let _: () = Self::TAGGABLE;
+            Self {
+                inner: AtomicPtr::new(ptr::null_mut()),
+                _phantom: PhantomData,
+            }
+        }
+    }
+
+    /// Atomically swaps out the current contents for `value`, returning the old contents.
+    ///
+    /// Storing a value marks the slot as ever set. Uses acquire–release ordering.
+    pub fn swap(&self, value: Option<Box<T>>) -> Option<Box<T>> {
+        match value {
+            Some(value) => {
+                let prev = self.inner.swap(tag(Box::into_raw(value)), Ordering::AcqRel);
+                // Safety: untagged non-null pointers in the slot come from `Box::into_raw`
+                unsafe { from_tagged(prev) }
+            }
+            None => self.take(),
+        }
+    }
+
+    /// Takes the current contents, leaving the slot empty.
+    ///
+    /// Taking does not reset [`was_ever_set`](Self::was_ever_set). Uses acquire–release
+    /// ordering.
+    ///
+    /// ```
+    /// # use atomic_slot::HistorySlot;
+    /// let slot = HistorySlot::new(Box::new(5u32));
+    /// assert_eq!(*slot.take().unwrap(), 5);
+    /// assert!(slot.is_none());
+    /// assert!(slot.was_ever_set());
+    /// ```
+    pub fn take(&self) -> Option<Box<T>> {
+        if untag(self.inner.load(Ordering::Acquire)).is_null() {
+            return None;
+        }
+        // A value was seen, so the history bit is already set and stays set
+        let prev = self
+            .inner
+            .swap(ptr::without_provenance_mut(EVER_SET), Ordering::AcqRel);
+        // Safety: untagged non-null pointers in the slot come from `Box::into_raw`
+        unsafe { from_tagged(prev) }
+    }
+
+    /// Stores `value` into the slot, dropping whatever was there before.
+    pub fn store(&self, value: Option<Box<T>>) {
+        let _ = self.swap(value);
+    }
+
+    /// Returns `true` if the slot currently contains a value. Uses acquire ordering.
+    pub fn is_some(&self) -> bool {
+        !self.is_none()
+    }
+
+    /// Returns `true` if the slot is empty. Uses acquire ordering.
+    pub fn is_none(&self) -> bool {
+        untag(self.inner.load(Ordering::Acquire)).is_null()
+    }
+
+    /// Returns `true` if a value was ever stored in the slot, even if it has since been taken.
+    ///
+    /// Uses acquire ordering.
+    pub fn was_ever_set(&self) -> bool {
+        self.inner.load(Ordering::Acquire).addr() & EVER_SET != 0
+    }
+}
+
+impl<T> Drop for HistorySlot<T> {
+    fn drop(&mut self) {
+        // Safety: untagged non-null pointers in the slot come from `Box::into_raw`
+        drop(unsafe { from_tagged(with_ptr_mut(&mut self.inner, |ptr| *ptr)) });
+    }
+}
+
+fn tag<T>(ptr: *mut T) -> *mut T {
+    ptr.map_addr(|addr| addr | EVER_SET)
+}
+
+fn untag<T>(ptr: *mut T) -> *mut T {
+    ptr.map_addr(|addr| addr & !EVER_SET)
+}
+
+/// # Safety
+///
+/// Once untagged, `ptr` must be null or come from `Box::into_raw` and not be owned elsewhere.
+unsafe fn from_tagged<T>(ptr: *mut T) -> Option<Box<T>> {
+    let ptr = untag(ptr);
+    if ptr.is_null() {
+        None
+    } else {
+        // Safety: guaranteed by the caller
+        Some(unsafe { Box::from_raw(ptr) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_bit_survives_take() {
+        let slot = HistorySlot::<u64>::empty();
+        assert!(slot.is_none());
+        assert!(!slot.was_ever_set());
+        assert!(slot.take().is_none());
+        assert!(!slot.was_ever_set());
+
+        slot.store(Some(Box::new(3)));
+        assert!(slot.is_some());
+        assert!(slot.was_ever_set());
+
+        assert_eq!(*slot.take().unwrap(), 3);
+        assert!(slot.is_none());
+        assert!(slot.was_ever_set());
+
+        assert!(slot.swap(Some(Box::new(4))).is_none());
+        assert_eq!(*slot.swap(None).unwrap(), 4);
+        assert!(slot.was_ever_set());
+    }
+}
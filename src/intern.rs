@@ -0,0 +1,95 @@
+//! Deduplicating values taken out of slots.
+
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+
+use crate::AtomicSlot;
+
+/// A minimal concurrent intern table handing out one shared `Arc<T>` per distinct value.
+///
+/// Lookups and insertions take a single lock, so the table suits staging values that are
+/// interned occasionally rather than on a hot path. Entries are never evicted.
+pub struct InternTable<T> {
+    entries: Mutex<HashSet<Arc<T>>>,
+}
+
+impl<T: Hash + Eq> Default for InternTable<T> {
+    /// Creates an empty `InternTable<T>`.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Hash + Eq> InternTable<T> {
+    /// Creates an empty `InternTable`.
+    pub fn new() -> Self {
+        Self {
+            entries: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Returns the shared entry equal to `value`, inserting `value` if there is none.
+    ///
+    /// If an equal entry already exists, `value` is dropped.
+    pub fn intern(&self, value: Box<T>) -> Arc<T> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(existing) = entries.get(&*value) {
+            return existing.clone();
+        }
+        let entry = Arc::<T>::from(value);
+        entries.insert(entry.clone());
+        entry
+    }
+
+    /// Returns the number of distinct values in the table.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Returns `true` if nothing was interned yet.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: Hash + Eq> AtomicSlot<T> {
+    /// Takes the current contents and interns them into `table`, returning the shared entry.
+    ///
+    /// If `table` already holds an equal value, the taken box is dropped and the existing
+    /// entry is returned.
+    ///
+    /// ```
+    /// # use atomic_slot::{AtomicSlot, InternTable};
+    /// let table = InternTable::new();
+    /// let slot = AtomicSlot::new(Box::new(String::from("GET")));
+    /// let method = slot.take_intern(&table).unwrap();
+    /// assert_eq!(*method, "GET");
+    /// assert!(slot.take_intern(&table).is_none());
+    /// ```
+    pub fn take_intern(&self, table: &InternTable<T>) -> Option<Arc<T>> {
+        self.take().map(|value| table.intern(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equal_values_share_one_arc() {
+        let table = InternTable::new();
+        let slot = AtomicSlot::new(Box::new(String::from("host")));
+        let first = slot.take_intern(&table).unwrap();
+
+        slot.store(Some(Box::new(String::from("host"))));
+        let second = slot.take_intern(&table).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(table.len(), 1);
+
+        slot.store(Some(Box::new(String::from("port"))));
+        let third = slot.take_intern(&table).unwrap();
+        assert!(!Arc::ptr_eq(&first, &third));
+        assert_eq!(table.len(), 2);
+    }
+}
@@ -1,4 +1,5 @@
 #![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
 #![deny(missing_docs)]
 
 //! A simple, lock-free, atomic slot for transferring ownership of `Box<T>`.
@@ -17,14 +18,135 @@
 //! assert!(slot.is_none());
 //! ```
 
+#[cfg(not(feature = "alloc"))]
+// Breaking since 0.2, which made `alloc` its own feature; see CHANGELOG.md
+compile_error!(
+    "atomic-slot needs the `alloc` feature, or `std` which enables it; \
+     with `default-features = false`, add `features = [\"alloc\"]`"
+);
+
 #[cfg(not(feature = "std"))]
 extern crate alloc;
 #[cfg(not(feature = "std"))]
-use alloc::boxed::Box;
+use alloc::{boxed::Box, string::String, vec::Vec};
 
 use core::marker::PhantomData;
+use core::mem::{ManuallyDrop, MaybeUninit};
 use core::ptr;
-use core::sync::atomic::{AtomicPtr, Ordering};
+
+use crate::sync::{AtomicPtr, Ordering};
+
+/// Keeps a `const fn` const, except under `cfg(loom)` whose atomics have no `const`
+/// constructors.
+macro_rules! loom_const_fn {
+    ($(#[$attr:meta])* $vis:vis const fn $($rest:tt)*) => {
+        #[cfg(not(loom))]
+        $(#[$attr])* $vis const fn $($rest)*
+        #[cfg(loom)]
+        $(#[$attr])* $vis fn $($rest)*
+    };
+}
+
+#[cfg(feature = "allocator_api")]
+mod alloc_in;
+#[cfg(target_has_atomic = "ptr")]
+mod arc_slot;
+mod array;
+#[cfg(feature = "bytemuck")]
+mod bytes;
+#[cfg(feature = "channel")]
+mod channel;
+#[cfg(feature = "std")]
+mod defer;
+mod generational;
+mod history;
+#[cfg(feature = "std")]
+mod intern;
+mod mailbox;
+#[cfg(feature = "std")]
+mod notify;
+mod once;
+mod padded;
+mod pool;
+mod publish;
+#[cfg(feature = "std")]
+mod rate;
+mod recycle;
+mod reserve;
+#[cfg(feature = "async")]
+mod response;
+mod rw_spin;
+mod seq;
+#[cfg(feature = "serde")]
+mod serde_impl;
+mod smart;
+#[cfg(feature = "spin-read")]
+mod spin_read;
+// `Arc` needs native pointer atomics
+#[cfg(target_has_atomic = "ptr")]
+mod split;
+mod sync;
+mod tagged;
+#[cfg(feature = "std")]
+mod timed;
+mod traced;
+#[cfg(feature = "debug-tracking")]
+mod tracking;
+mod unsized_slot;
+mod versioned;
+mod witness;
+
+#[cfg(feature = "allocator_api")]
+pub use alloc_in::AtomicSlotIn;
+#[cfg(target_has_atomic = "ptr")]
+pub use arc_slot::AtomicArcSlot;
+pub use array::AtomicSlotArray;
+#[cfg(feature = "bytemuck")]
+pub use bytes::{ByteOrder, ByteSlot};
+#[cfg(feature = "channel")]
+pub use channel::{SlotReceiver, SlotSender, slot_channel};
+#[cfg(feature = "std")]
+pub use defer::DeferredDropper;
+pub use generational::{GenSlot, Handle};
+pub use history::HistorySlot;
+#[cfg(feature = "std")]
+pub use intern::InternTable;
+pub use mailbox::AtomicMailbox;
+#[cfg(feature = "std")]
+pub use notify::{ChangedFuture, NotifySlot, TakeFuture};
+#[cfg(all(feature = "std", feature = "async"))]
+pub use notify::{Occupancy, OccupancyChanges};
+pub use once::OnceSlot;
+pub use padded::PaddedAtomicSlot;
+pub use pool::{
+    MoveOutcome, Pool, Pooled, fill_empty, fill_from_iter, move_if_empty, occupied_count, take_all,
+    take_all_into, take_batch_into, take_prioritized,
+};
+#[cfg(feature = "rayon")]
+pub use pool::{par_drain, par_retain};
+pub use publish::PublishSlot;
+#[cfg(feature = "std")]
+pub use rate::{RateLimited, TokenBucket};
+pub use recycle::Recycler;
+pub use reserve::{Reservation, ReserveSlot};
+#[cfg(feature = "async")]
+pub use response::{CancelHandle, Cancelled, Responder, ResponseFuture, ResponseSlot};
+pub use rw_spin::RwSpinSlot;
+pub use seq::SeqSlot;
+pub use smart::{NoUninit, SmallAtomicSlot, SmartSlot};
+#[cfg(feature = "spin-read")]
+pub use spin_read::SpinReadSlot;
+#[cfg(target_has_atomic = "ptr")]
+pub use split::{Consumer, Producer};
+pub use tagged::{TaggedSlot, TaggedVersionSlot, VersionMismatch};
+#[cfg(feature = "std")]
+pub use timed::TimedSlot;
+pub use traced::TracedSlot;
+#[cfg(feature = "debug-tracking")]
+pub use tracking::DebugSlot;
+pub use unsized_slot::{AtomicDynSlot, AtomicSlotUnsized};
+pub use versioned::VersionedSlot;
+pub use witness::{Witness, WitnessSlot};
 
 /// A lock-free, thread-safe slot that may contain a `Box<T>`.
 ///
@@ -33,11 +155,33 @@ use core::sync::atomic::{AtomicPtr, Ordering};
 /// For most use cases, the default methods—`swap`, `take`, and `store`—use
 /// acquire–release ordering.  If you need finer control, see the `_ordered`
 /// variants.
+///
+/// # Layout
+///
+/// `AtomicSlot<T>` is `#[repr(transparent)]` over `AtomicPtr<T>`, so it has the same size,
+/// alignment and ABI. A slot can be shared with C or hand-written atomic code as a single
+/// pointer, provided that code keeps the invariant: the pointer is either null (empty) or
+/// was produced by `Box::into_raw` and is owned by the slot.
+///
+/// # Zero-sized types
+///
+/// A `Box` of a zero-sized type never allocates: its pointer is the non-null
+/// [`NonNull::dangling`](core::ptr::NonNull::dangling) address, and dropping it frees nothing.
+/// An `AtomicSlot<()>` is therefore a plain atomic presence flag with no heap traffic, and a
+/// zero-sized `T` with a `Drop` impl still has its destructor run once for each stored value.
+/// Every such box has the same address, so identity comparisons like
+/// [`compare_exchange`](Self::compare_exchange) cannot tell two values apart.
+#[repr(transparent)]
 pub struct AtomicSlot<T> {
     inner: AtomicPtr<T>,
     _phantom: PhantomData<Option<Box<T>>>,
 }
 
+// Safety: the slot only moves `Box<T>` between threads and never shares `&T` through `&self`,
+// like `Mutex<T>`
+unsafe impl<T: Send> Send for AtomicSlot<T> {}
+unsafe impl<T: Send> Sync for AtomicSlot<T> {}
+
 impl<T> Default for AtomicSlot<T> {
     /// Creates an empty `AtomicSlot<T>`.
     fn default() -> Self {
@@ -45,6 +189,96 @@ impl<T> Default for AtomicSlot<T> {
     }
 }
 
+impl<T> From<Box<T>> for AtomicSlot<T> {
+    /// Creates a slot containing `value`.
+    fn from(value: Box<T>) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T> From<Option<Box<T>>> for AtomicSlot<T> {
+    /// Creates a slot containing `value`, or an empty slot for `None`.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let slot: AtomicSlot<i32> = None.into();
+    /// assert!(slot.is_none());
+    /// ```
+    fn from(value: Option<Box<T>>) -> Self {
+        match value {
+            Some(value) => Self::new(value),
+            None => Self::empty(),
+        }
+    }
+}
+
+impl<T> IntoIterator for AtomicSlot<T> {
+    type Item = Box<T>;
+    type IntoIter = core::option::IntoIter<Box<T>>;
+
+    /// Consumes the slot, yielding its contents if it has any.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let slots = [AtomicSlot::new(Box::new(1)), AtomicSlot::empty()];
+    /// let values: Vec<_> = slots.into_iter().flatten().map(|b| *b).collect();
+    /// assert_eq!(values, [1]);
+    /// ```
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_inner().into_iter()
+    }
+}
+
+impl<T> core::fmt::Debug for AtomicSlot<T> {
+    /// Shows only whether the slot is occupied, since the value may be taken and freed by
+    /// another thread while it is being formatted.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let slot = AtomicSlot::new(Box::new(1));
+    /// assert_eq!(format!("{slot:?}"), "AtomicSlot { occupied: true }");
+    /// ```
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AtomicSlot")
+            .field("occupied", &self.is_some())
+            .finish()
+    }
+}
+
+impl<T> PartialEq for AtomicSlot<T> {
+    /// Compares occupancy only: two slots are equal if both are empty or both are full.
+    ///
+    /// The values cannot be compared through `&self`, since another thread may take and free
+    /// them meanwhile; use [`eq_exclusive`](AtomicSlot::eq_exclusive) for that.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// assert_eq!(AtomicSlot::new(Box::new(1)), AtomicSlot::new(Box::new(2)));
+    /// assert_ne!(AtomicSlot::new(Box::new(1)), AtomicSlot::empty());
+    /// ```
+    fn eq(&self, other: &Self) -> bool {
+        self.is_some() == other.is_some()
+    }
+}
+
+impl<T> Eq for AtomicSlot<T> {}
+
+impl<T> core::hash::Hash for AtomicSlot<T> {
+    /// Hashes occupancy only, consistent with `PartialEq`. The value is not hashed, since
+    /// another thread may take and free it while it is being read.
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.is_some().hash(state);
+    }
+}
+
+impl<T> Drop for AtomicSlot<T> {
+    /// Drops the contents, if any.
+    fn drop(&mut self) {
+        // Safety: pointers in the slot come from `Box::into_raw` and are owned by the slot
+        drop(unsafe { into_box(self.ptr_mut()) });
+    }
+}
+
 impl<T> AtomicSlot<T> {
     /// Creates a new `AtomicSlot` containing `value`.
     ///
@@ -60,32 +294,106 @@ impl<T> AtomicSlot<T> {
         }
     }
 
-    /// Creates an empty `AtomicSlot` (contains no value).
+    loom_const_fn! {
+        /// Creates an empty `AtomicSlot` (contains no value).
+        ///
+        /// Being `const`, this can initialize large `static` pools of slots:
+        ///
+        /// ```
+        /// # use atomic_slot::AtomicSlot;
+        /// static POOL: [AtomicSlot<u64>; 1024] = [const { AtomicSlot::empty() }; 1024];
+        /// assert!(POOL.iter().all(AtomicSlot::is_none));
+        /// ```
+        ///
+        /// ```
+        /// # use atomic_slot::AtomicSlot;
+        /// let slot: AtomicSlot<i32> = AtomicSlot::empty();
+        /// assert!(slot.is_none());
+        /// ```
+        pub const fn empty() -> Self {
+            Self {
+                inner: AtomicPtr::new(ptr::null_mut()),
+                _phantom: PhantomData,
+            }
+        }
+    }
+
+    /// Compile-time check that an all-zero bit pattern is a valid empty slot.
+    ///
+    /// A slot is a single pointer-sized word and a null pointer is all zeros.
+    const ZEROED_IS_EMPTY: () = assert!(size_of::<Self>() == size_of::<*mut T>());
+
+    /// Initializes the slot at `slot` to empty, without constructing it elsewhere first.
+    ///
+    /// Any previous contents of the memory are overwritten without being dropped.
+    ///
+    /// # Safety
+    ///
+    /// `slot` must be valid for writes and properly aligned.
     ///
     /// ```
     /// # use atomic_slot::AtomicSlot;
-    /// let slot: AtomicSlot<i32> = AtomicSlot::empty();
+    /// # use std::mem::MaybeUninit;
+    /// let mut mem = MaybeUninit::<AtomicSlot<i32>>::uninit();
+    /// unsafe { AtomicSlot::init_in_place(mem.as_mut_ptr()) };
+    /// let slot = unsafe { mem.assume_init() };
     /// assert!(slot.is_none());
     /// ```
-    pub fn empty() -> Self {
-        Self {
-            inner: AtomicPtr::new(ptr::null_mut()),
-            _phantom: PhantomData,
-        }
+    pub unsafe fn init_in_place(slot: *mut Self) {
+        // Safety: the caller guarantees `slot` is valid for writes
+        unsafe { slot.write(Self::empty()) }
+    }
+
+    /// Treats zero-initialized memory as an empty slot.
+    ///
+    /// An all-zero bit pattern is a valid empty slot, so memory that is already zeroed (for
+    /// example a pool placed in `.bss` or obtained from `alloc_zeroed`) needs no further
+    /// initialization.
+    ///
+    /// # Safety
+    ///
+    /// Every byte of `mem` must be zero.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// # use std::mem::MaybeUninit;
+    /// let mut mem = MaybeUninit::<AtomicSlot<i32>>::zeroed();
+    /// let slot = unsafe { AtomicSlot::from_zeroed(&mut mem) };
+    /// assert!(slot.is_none());
+    /// ```
+    pub unsafe fn from_zeroed(mem: &mut MaybeUninit<Self>) -> &mut Self {
+        let _: () = Self::ZEROED_IS_EMPTY;
+        // Safety: the caller guarantees `mem` is zeroed, which is a valid empty slot
+        unsafe { mem.assume_init_mut() }
+    }
+
+    /// Returns a [`SlotInit`] builder for constructing a slot with conditional initial contents.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let slot = AtomicSlot::builder().value_if(true, || Box::new(3)).build();
+    /// assert_eq!(*slot.take().unwrap(), 3);
+    /// ```
+    pub fn builder() -> SlotInit<T> {
+        SlotInit { value: None }
     }
 
     /// Atomically swaps out the current contents for `value`, returning the old contents.
     ///
-    /// Uses acquire–release ordering.
+    /// `value` may be a bare `Box<T>` as well as an `Option<Box<T>>`. Uses acquire–release
+    /// ordering.
     ///
     /// ```
     /// # use atomic_slot::AtomicSlot;
     /// let slot = AtomicSlot::new(Box::new(1));
     /// let old = slot.swap(Some(Box::new(2)));
     /// assert_eq!(*old.unwrap(), 1);
+    /// assert_eq!(*slot.swap(Box::new(3)).unwrap(), 2);
+    /// assert_eq!(*slot.swap(None).unwrap(), 3);
     /// ```
-    pub fn swap(&self, value: Option<Box<T>>) -> Option<Box<T>> {
-        self.swap_ordered(value, Ordering::AcqRel)
+    #[must_use = "the old contents are dropped if unused; use `store` to drop them on purpose"]
+    pub fn swap(&self, value: impl Into<Option<Box<T>>>) -> Option<Box<T>> {
+        self.swap_ordered(value.into(), Ordering::AcqRel)
     }
 
     /// Takes the current contents, leaving the slot empty.
@@ -97,27 +405,61 @@ impl<T> AtomicSlot<T> {
     /// let slot = AtomicSlot::new(Box::new(7));
     /// assert_eq!(*slot.take().unwrap(), 7);
     /// ```
+    ///
+    /// Discarding the taken value is almost always a bug, so it is flagged:
+    ///
+    /// ```compile_fail
+    /// #![deny(unused_must_use)]
+    /// # use atomic_slot::AtomicSlot;
+    /// let slot = AtomicSlot::new(Box::new(7));
+    /// slot.take();
+    /// ```
+    #[must_use = "the taken value is dropped if unused; use `store(None)` to clear the slot"]
     pub fn take(&self) -> Option<Box<T>> {
         self.take_ordered(Ordering::AcqRel)
     }
 
     /// Stores `value` into the slot, dropping whatever was there before.
     ///
-    /// Uses acquire–release ordering.
+    /// `value` may be a bare `Box<T>` as well as an `Option<Box<T>>`. Uses acquire–release
+    /// ordering.
     ///
     /// ```
     /// # use atomic_slot::AtomicSlot;
     /// let slot: AtomicSlot<i32> = AtomicSlot::empty();
     /// slot.store(Some(Box::new(5)));
     /// assert_eq!(*slot.take().unwrap(), 5);
+    /// slot.store(Box::new(6));
+    /// slot.store(None);
+    /// assert!(slot.is_none());
+    /// ```
+    pub fn store(&self, value: impl Into<Option<Box<T>>>) {
+        self.store_ordered(value.into(), Ordering::AcqRel)
+    }
+
+    /// Installs `value`, returning the old contents.
+    ///
+    /// The same as [`swap`](Self::swap) without the `#[must_use]`, for callers who read
+    /// `replace` more naturally, as with [`Option::replace`]. Uses acquire–release ordering.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let slot = AtomicSlot::new(Box::new(1));
+    /// let old = slot.replace(Box::new(2));
+    /// assert_eq!(*old.unwrap(), 1);
+    /// assert_eq!(*slot.replace(Some(Box::new(3))).unwrap(), 2);
+    /// assert_eq!(*slot.replace(None).unwrap(), 3);
     /// ```
-    pub fn store(&self, value: Option<Box<T>>) {
-        self.store_ordered(value, Ordering::AcqRel)
+    pub fn replace(&self, value: impl Into<Option<Box<T>>>) -> Option<Box<T>> {
+        self.swap_ordered(value.into(), Ordering::AcqRel)
     }
 
     /// Atomically swaps out the current contents for `value`, returning the old contents,
     /// with the specified memory `order`.
     ///
+    /// Installing a value needs release semantics so that whoever takes it sees the box
+    /// initialized; in debug builds, a `Some` value with `Relaxed` or `Acquire` panics.
+    ///
     /// ```
     /// # use atomic_slot::AtomicSlot;
     /// # use std::sync::atomic::Ordering;
@@ -125,7 +467,12 @@ impl<T> AtomicSlot<T> {
     /// let old = slot.swap_ordered(Some(Box::new(4)), Ordering::SeqCst);
     /// assert_eq!(*old.unwrap(), 3);
     /// ```
+    #[must_use = "the old contents are dropped if unused; use `store_ordered` to drop them on purpose"]
+    #[inline]
     pub fn swap_ordered(&self, value: Option<Box<T>>, order: Ordering) -> Option<Box<T>> {
+        if value.is_some() {
+            debug_assert_publishing(order);
+        }
         let raw = value.map(Box::into_raw).unwrap_or(ptr::null_mut());
         let prev = self.inner.swap(raw, order);
         if prev.is_null() {
@@ -136,6 +483,96 @@ impl<T> AtomicSlot<T> {
         }
     }
 
+    /// Like [`swap_ordered`](Self::swap_ordered), but also reports how the occupancy of the
+    /// slot changed.
+    ///
+    /// The transition is derived from the pointer the swap replaced, so it is exact: a
+    /// producer that sees [`Transition::EmptyToFull`] knows it was the one that filled the
+    /// slot, for example to enqueue a single wake-up, without a racy `is_none` check first.
+    ///
+    /// ```
+    /// # use atomic_slot::{AtomicSlot, Transition};
+    /// # use std::sync::atomic::Ordering;
+    /// let slot = AtomicSlot::empty();
+    /// let (_, t) = slot.swap_detect(Some(Box::new(1)), Ordering::AcqRel);
+    /// assert_eq!(t, Transition::EmptyToFull);
+    /// let (old, t) = slot.swap_detect(Some(Box::new(2)), Ordering::AcqRel);
+    /// assert_eq!((*old.unwrap(), t), (1, Transition::FullToFull));
+    /// ```
+    pub fn swap_detect(
+        &self,
+        value: Option<Box<T>>,
+        order: Ordering,
+    ) -> (Option<Box<T>>, Transition) {
+        let filling = value.is_some();
+        let prev = self.swap_ordered(value, order);
+        let transition = match (prev.is_some(), filling) {
+            (false, true) => Transition::EmptyToFull,
+            (true, true) => Transition::FullToFull,
+            (true, false) => Transition::FullToEmpty,
+            (false, false) => Transition::EmptyToEmpty,
+        };
+        (prev, transition)
+    }
+
+    /// Installs `value`, returning the old contents, with the specified memory `order`.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// # use std::sync::atomic::Ordering;
+    /// let slot = AtomicSlot::empty();
+    /// assert!(slot.replace_ordered(Box::new(4), Ordering::SeqCst).is_none());
+    /// assert_eq!(*slot.take().unwrap(), 4);
+    /// ```
+    pub fn replace_ordered(&self, value: Box<T>, order: Ordering) -> Option<Box<T>> {
+        self.swap_ordered(Some(value), order)
+    }
+
+    /// Takes the current contents with a single compare-and-swap, reporting why nothing was
+    /// taken.
+    ///
+    /// Returns [`TakeError::Empty`] if the slot was seen empty, in which case no exchange was
+    /// attempted, and [`TakeError::Contended`] if another thread changed the slot between the
+    /// load and the exchange. A contended slot may well hold a value, so retrying is worthwhile;
+    /// an empty one is only filled by another producer.
+    ///
+    /// ```
+    /// # use atomic_slot::{AtomicSlot, TakeError};
+    /// let slot = AtomicSlot::new(Box::new(1));
+    /// assert_eq!(*slot.try_take().unwrap(), 1);
+    /// assert_eq!(slot.try_take().unwrap_err(), TakeError::Empty);
+    /// ```
+    pub fn try_take(&self) -> Result<Box<T>, TakeError> {
+        let current = self.inner.load(Ordering::Acquire);
+        if current.is_null() {
+            return Err(TakeError::Empty);
+        }
+        self.inner
+            .compare_exchange(
+                current,
+                ptr::null_mut(),
+                Ordering::AcqRel,
+                Ordering::Relaxed,
+            )
+            // Safety: the exchange moved ownership of `current` out of the slot
+            .map(|_| unsafe { Box::from_raw(current) })
+            .map_err(|_| TakeError::Contended)
+    }
+
+    /// Takes the current contents as an iterator yielding at most one value.
+    ///
+    /// The value is taken right away, when `drain` is called. Uses acquire–release ordering.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let mailboxes = [AtomicSlot::new(Box::new(1)), AtomicSlot::empty(), AtomicSlot::new(Box::new(3))];
+    /// let sum: i32 = mailboxes.iter().flat_map(AtomicSlot::drain).map(|b| *b).sum();
+    /// assert_eq!(sum, 4);
+    /// ```
+    pub fn drain(&self) -> impl Iterator<Item = Box<T>> + use<T> {
+        self.take().into_iter()
+    }
+
     /// Takes the current contents, leaving the slot empty, with the specified memory `order`.
     ///
     /// ```
@@ -144,6 +581,7 @@ impl<T> AtomicSlot<T> {
     /// let slot = AtomicSlot::new(Box::new(8));
     /// assert_eq!(*slot.take_ordered(Ordering::Acquire).unwrap(), 8);
     /// ```
+    #[must_use = "the taken value is dropped if unused; use `store_ordered(None, ..)` to clear the slot"]
     pub fn take_ordered(&self, order: Ordering) -> Option<Box<T>> {
         self.swap_ordered(None, order)
     }
@@ -151,6 +589,9 @@ impl<T> AtomicSlot<T> {
     /// Stores `value` into the slot, dropping whatever was there before,
     /// with the specified memory `order`.
     ///
+    /// As with [`swap_ordered`](Self::swap_ordered), storing a `Some` value without release
+    /// semantics panics in debug builds.
+    ///
     /// ```
     /// # use atomic_slot::AtomicSlot;
     /// # use std::sync::atomic::Ordering;
@@ -161,6 +602,75 @@ impl<T> AtomicSlot<T> {
         let _ = self.swap_ordered(value, order);
     }
 
+    /// Stores `value` only if the slot is empty, handing it back in `Err` otherwise.
+    ///
+    /// Unlike [`store`](Self::store), this never drops an existing value, so it suits one-shot
+    /// results that must not be clobbered. Uses acquire–release ordering.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let slot = AtomicSlot::empty();
+    /// assert!(slot.try_store(Box::new(1)).is_ok());
+    /// assert_eq!(*slot.try_store(Box::new(2)).unwrap_err(), 2);
+    /// assert_eq!(*slot.take().unwrap(), 1);
+    /// ```
+    pub fn try_store(&self, value: Box<T>) -> Result<(), Box<T>> {
+        self.try_store_ordered(value, Ordering::AcqRel, Ordering::Acquire)
+    }
+
+    /// Fills the empty slot with `value`, handing it back in `Err` if the slot is already full.
+    ///
+    /// The producer's counterpart to [`take`](Self::take): a producer fills, a consumer
+    /// takes, and neither ever drops the other's value. Same as [`try_store`](Self::try_store).
+    /// Uses acquire–release ordering.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let slot = AtomicSlot::empty();
+    /// slot.fill(Box::new("request")).unwrap();
+    /// assert_eq!(*slot.fill(Box::new("another")).unwrap_err(), "another");
+    /// assert_eq!(*slot.take().unwrap(), "request");
+    /// assert!(slot.fill(Box::new("another")).is_ok());
+    /// ```
+    pub fn fill(&self, value: Box<T>) -> Result<(), Box<T>> {
+        self.try_store(value)
+    }
+
+    /// Stores `value` only if the slot is empty, with the specified memory orderings.
+    ///
+    /// `success` applies when the value is stored and `failure` when the slot is occupied,
+    /// as for [`AtomicPtr::compare_exchange`].
+    ///
+    /// # Panics
+    ///
+    /// With debug assertions, panics if `failure` is `Release` or `AcqRel`, or stronger than
+    /// `success`.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// # use std::sync::atomic::Ordering;
+    /// let slot = AtomicSlot::empty();
+    /// assert!(slot.try_store_ordered(Box::new(1), Ordering::Release, Ordering::Relaxed).is_ok());
+    /// ```
+    pub fn try_store_ordered(
+        &self,
+        value: Box<T>,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<(), Box<T>> {
+        debug_assert_cas_orderings(success, failure);
+        debug_assert_publishing(success);
+        let raw = Box::into_raw(value);
+        match self
+            .inner
+            .compare_exchange(ptr::null_mut(), raw, success, failure)
+        {
+            Ok(_) => Ok(()),
+            // Safety: `raw` came from `Box::into_raw` above and was not installed
+            Err(_) => Err(unsafe { Box::from_raw(raw) }),
+        }
+    }
+
     /// Returns `true` if the slot currently contains a value.
     ///
     /// Uses acquire ordering.
@@ -194,6 +704,27 @@ impl<T> AtomicSlot<T> {
         self.is_none_ordered(Ordering::Acquire)
     }
 
+    /// Returns `true` if the slot currently contains a value, with the specified memory
+    /// `order`.
+    ///
+    /// With `Relaxed` this is a single plain atomic load without any fence, which suits
+    /// polling loops that [`take`](Self::take) once they see a value: the take itself
+    /// synchronizes with the producer.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// # use std::sync::atomic::Ordering;
+    /// let slot = AtomicSlot::new(Box::new(1));
+    /// while !slot.is_some_ordered(Ordering::Relaxed) {
+    ///     std::hint::spin_loop();
+    /// }
+    /// assert_eq!(*slot.take().unwrap(), 1);
+    /// ```
+    #[inline]
+    pub fn is_some_ordered(&self, order: Ordering) -> bool {
+        !self.is_none_ordered(order)
+    }
+
     /// Returns `true` if the slot is empty, with the specified memory `order`.
     ///
     /// ```
@@ -202,37 +733,2293 @@ impl<T> AtomicSlot<T> {
     /// let slot: AtomicSlot<i32> = AtomicSlot::empty();
     /// assert!(slot.is_none_ordered(Ordering::Relaxed));
     /// ```
+    #[inline]
     pub fn is_none_ordered(&self, order: Ordering) -> bool {
         self.inner.load(order).is_null()
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::sync::Arc;
-    use std::thread;
+    /// Takes the current contents along with an estimate of their size in bytes.
+    ///
+    /// The estimate is `size_of::<T>()`, i.e. the size of the boxed allocation. Heap memory
+    /// owned by `T` (such as the buffer of a `Vec`) is not included.
+    ///
+    /// Uses acquire–release ordering.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let slot = AtomicSlot::new(Box::new([0u8; 32]));
+    /// let (value, size) = slot.take_with_size().unwrap();
+    /// assert_eq!(value.len(), 32);
+    /// assert_eq!(size, 32);
+    /// ```
+    pub fn take_with_size(&self) -> Option<(Box<T>, usize)> {
+        self.take().map(|value| (value, size_of::<T>()))
+    }
 
-    #[test]
-    fn sequential_swap_and_take() {
-        let slot = AtomicSlot::new(Box::new(10));
+    /// Takes the current contents and checks them against an `expected` checksum.
+    ///
+    /// Returns `None` if the slot is empty. Otherwise the value is taken either way, and is
+    /// returned in `Ok` if `checksum(&value) == expected`, or in `Err` so the caller can
+    /// handle the corruption.
+    ///
+    /// Uses acquire–release ordering.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let sum = |v: &Vec<u8>| v.iter().map(|&b| u64::from(b)).sum();
+    /// let slot = AtomicSlot::new(Box::new(vec![1, 2, 3]));
+    /// assert_eq!(*slot.take_verified(6, sum).unwrap().unwrap(), [1, 2, 3]);
+    /// assert!(slot.take_verified(6, sum).is_none());
+    /// ```
+    pub fn take_verified<F>(&self, expected: u64, checksum: F) -> Option<Result<Box<T>, Box<T>>>
+    where
+        F: FnOnce(&T) -> u64,
+    {
+        let value = self.take()?;
+        if checksum(&value) == expected {
+            Some(Ok(value))
+        } else {
+            Some(Err(value))
+        }
+    }
 
-        let old = slot.swap(Some(Box::new(20)));
-        assert_eq!(*old.unwrap(), 10);
+    /// Takes the current contents and runs `finalize` on them before handing them back.
+    ///
+    /// This provides a deterministic pre-drop step, such as flushing a buffer, on the taking
+    /// thread. The box is not freed: the caller still owns it and decides when to drop it.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let slot = AtomicSlot::new(Box::new(vec![1, 2, 3]));
+    /// let flushed = slot.take_with_finalizer(|buf| buf.clear()).unwrap();
+    /// assert!(flushed.is_empty());
+    /// ```
+    pub fn take_with_finalizer(&self, finalize: impl FnOnce(&mut T)) -> Option<Box<T>> {
+        let mut value = self.take()?;
+        finalize(&mut value);
+        Some(value)
+    }
 
-        let taken = slot.take();
-        assert_eq!(*taken.unwrap(), 20);
-        assert!(slot.is_none());
+    /// Takes the current contents along with a routing key computed by `classify`.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let slot = AtomicSlot::new(Box::new(-3));
+    /// let (negative, value) = slot.take_route(|v| *v < 0).unwrap();
+    /// assert!(negative);
+    /// assert_eq!(*value, -3);
+    /// ```
+    pub fn take_route<K, F>(&self, classify: F) -> Option<(K, Box<T>)>
+    where
+        F: FnOnce(&T) -> K,
+    {
+        let value = self.take()?;
+        Some((classify(&value), value))
     }
 
-    #[test]
-    fn sequential_empty_store() {
-        let slot = AtomicSlot::<i32>::empty();
-        assert!(slot.is_none());
+    /// Takes the current contents and passes them to `f` along with `ctx`, returning the
+    /// result.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let arena = vec![10, 20, 30];
+    /// let slot = AtomicSlot::new(Box::new(1usize));
+    /// assert_eq!(slot.take_with_ctx(&arena, |i, arena| arena[*i]), Some(20));
+    /// ```
+    pub fn take_with_ctx<C, R, F>(&self, ctx: &C, f: F) -> Option<R>
+    where
+        F: FnOnce(Box<T>, &C) -> R,
+    {
+        self.take().map(|value| f(value, ctx))
+    }
 
-        slot.store(Some(Box::new(5)));
-        assert!(slot.is_some());
-        assert_eq!(*slot.take().unwrap(), 5);
+    /// Takes the current contents and reinterprets the allocation as a `Box<U>`.
+    ///
+    /// This is zero-copy type punning, for example between a byte array and a `#[repr(C)]`
+    /// frame of the same layout. Layout equality is checked with a debug assertion.
+    ///
+    /// Uses acquire–release ordering.
+    ///
+    /// # Safety
+    ///
+    /// `T` and `U` must have the same size and alignment, so the allocation can be freed as a
+    /// `U`, and every value of `T` the slot may hold must be a valid value of `U`, as for
+    /// [`core::mem::transmute`].
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let slot = AtomicSlot::new(Box::new(1.5f32.to_bits()));
+    /// let value: Box<f32> = unsafe { slot.take_transmute() }.unwrap();
+    /// assert_eq!(*value, 1.5);
+    /// ```
+    pub unsafe fn take_transmute<U>(&self) -> Option<Box<U>> {
+        debug_assert!(
+            size_of::<T>() == size_of::<U>() && align_of::<T>() == align_of::<U>(),
+            "take_transmute requires T and U to have the same size and alignment"
+        );
+        let value = self.take()?;
+        // Safety: the caller guarantees the allocation is a valid, identically laid out `U`
+        Some(unsafe { Box::from_raw(Box::into_raw(value).cast::<U>()) })
+    }
+
+    /// Creates a slot owning `ptr`, or an empty slot if `ptr` is null.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be null or come from `Box::into_raw` for a `Box<T>` that nothing else
+    /// owns, for example a pointer returned by [`take_raw`](AtomicSlot::take_raw). The slot
+    /// frees it, so it must not be freed elsewhere.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let ptr = Box::into_raw(Box::new(5));
+    /// let slot = unsafe { AtomicSlot::from_raw(ptr) };
+    /// assert_eq!(*slot.take().unwrap(), 5);
+    /// ```
+    pub unsafe fn from_raw(ptr: *mut T) -> Self {
+        Self {
+            inner: AtomicPtr::new(ptr),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Creates a slot owning `ptr`, or an empty slot if `ptr` is null.
+    ///
+    /// This is [`from_raw`](AtomicSlot::from_raw) under a name that makes the null case
+    /// visible at the call site, for pointers that may legitimately be null, such as ones
+    /// handed over by FFI.
+    ///
+    /// # Safety
+    ///
+    /// A non-null `ptr` must come from `Box::into_raw` for a `Box<T>` that nothing else owns.
+    /// The slot frees it, so it must not be freed elsewhere.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let slot = unsafe { AtomicSlot::<i32>::from_raw_opt(std::ptr::null_mut()) };
+    /// assert!(slot.is_none());
+    ///
+    /// let slot = unsafe { AtomicSlot::from_raw_opt(Box::into_raw(Box::new(5))) };
+    /// assert_eq!(*slot.take().unwrap(), 5);
+    /// ```
+    pub unsafe fn from_raw_opt(ptr: *mut T) -> Self {
+        // Safety: forwarded from the caller
+        unsafe { Self::from_raw(ptr) }
+    }
+
+    /// Returns the current pointer, null if the slot is empty, without taking ownership.
+    ///
+    /// The pointer stays owned by the slot and may be freed by any thread that takes it, so
+    /// dereferencing it is only sound while the caller rules out concurrent takes, swaps and
+    /// stores.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// # use std::sync::atomic::Ordering;
+    /// let slot = AtomicSlot::new(Box::new(1));
+    /// let ptr = slot.as_ptr(Ordering::Acquire);
+    /// assert_eq!(ptr, &*slot.take().unwrap() as *const i32 as *mut i32);
+    /// assert!(slot.as_ptr(Ordering::Acquire).is_null());
+    /// ```
+    pub fn as_ptr(&self, order: Ordering) -> *mut T {
+        self.inner.load(order)
+    }
+
+    /// Takes the current contents as a raw pointer, null if the slot was empty, leaving the
+    /// slot empty with the specified memory `order`.
+    ///
+    /// # Safety
+    ///
+    /// The caller takes ownership of the pointer, which came from `Box::into_raw`. It must
+    /// be freed exactly once, for instance with `Box::from_raw` or by handing it back to
+    /// [`from_raw`](AtomicSlot::from_raw), or it leaks.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// # use std::sync::atomic::Ordering;
+    /// let slot = AtomicSlot::new(Box::new(2));
+    /// let ptr = unsafe { slot.take_raw(Ordering::AcqRel) };
+    /// assert!(slot.is_none());
+    /// assert_eq!(*unsafe { Box::from_raw(ptr) }, 2);
+    /// ```
+    pub unsafe fn take_raw(&self, order: Ordering) -> *mut T {
+        self.inner.swap(ptr::null_mut(), order)
+    }
+
+    /// Returns the underlying `AtomicPtr`, for running custom atomic operations on the slot,
+    /// such as moving a pointer into another lock-free structure with a single exchange.
+    ///
+    /// With the `portable-atomic` feature this is `portable_atomic::AtomicPtr`.
+    ///
+    /// # Safety
+    ///
+    /// The slot owns whatever pointer it holds. Every pointer stored through the returned
+    /// reference must be null or come from `Box::into_raw` for a `Box<T>` that nothing else
+    /// owns, and a pointer removed through it becomes owned by the caller.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// # use std::sync::atomic::Ordering;
+    /// let slot = AtomicSlot::empty();
+    /// let raw = Box::into_raw(Box::new(5));
+    /// let atomic = unsafe { slot.as_atomic_ptr() };
+    /// assert!(atomic.compare_exchange(std::ptr::null_mut(), raw, Ordering::AcqRel, Ordering::Acquire).is_ok());
+    /// assert_eq!(*slot.take().unwrap(), 5);
+    /// ```
+    pub unsafe fn as_atomic_ptr(&self) -> &AtomicPtr<T> {
+        &self.inner
+    }
+
+    /// Creates a slot owning the pointer held by `ptr`, which is empty if it is null.
+    ///
+    /// # Safety
+    ///
+    /// The pointer held by `ptr` must be null or come from `Box::into_raw` for a `Box<T>`
+    /// that nothing else owns, as with [`from_raw`](AtomicSlot::from_raw).
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let atomic = Box::into_raw(Box::new(3)).into();
+    /// let slot = unsafe { AtomicSlot::from_atomic_ptr(atomic) };
+    /// assert_eq!(*slot.take().unwrap(), 3);
+    /// ```
+    pub unsafe fn from_atomic_ptr(ptr: AtomicPtr<T>) -> Self {
+        Self {
+            inner: ptr,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Lends the contents to `f` as a raw pointer and stores the pointer `f` hands back.
+    ///
+    /// The slot's pointer (null if empty) is taken out, leaving the slot empty, and passed to
+    /// `f`, which may use, free or replace it, for example by handing it to C code. The pointer
+    /// returned by `f` is then owned by the slot again. If another thread stored a value in the
+    /// meantime, that newer value wins and the returned pointer is freed.
+    ///
+    /// Uses acquire–release ordering.
+    ///
+    /// # Safety
+    ///
+    /// The pointer returned by `f` must be null or come from `Box::<T>::into_raw` and not be
+    /// owned by anything else. In particular, if `f` frees the pointer it was given, it must
+    /// not return it.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let slot = AtomicSlot::new(Box::new(20));
+    /// let double = |ptr: *mut i32| {
+    ///     let value = unsafe { &mut *ptr };
+    ///     *value *= 2;
+    ///     (ptr, *value)
+    /// };
+    /// let doubled = unsafe { slot.with_raw_ownership(double) };
+    /// assert_eq!(doubled, 40);
+    /// assert_eq!(*slot.take().unwrap(), 40);
+    /// ```
+    pub unsafe fn with_raw_ownership<R>(&self, f: impl FnOnce(*mut T) -> (*mut T, R)) -> R {
+        let lent = self.inner.swap(ptr::null_mut(), Ordering::AcqRel);
+        let (returned, result) = f(lent);
+        // Safety: the caller guarantees `returned` is null or an owned `Box::into_raw` pointer
+        if let Some(value) = unsafe { into_box(returned) } {
+            self.restore(value);
+        }
+        result
+    }
+
+    /// Replaces the contents with the result of `f`, retrying on contention, like
+    /// [`AtomicPtr::fetch_update`].
+    ///
+    /// `f` is given a reference to the current value and returns the new contents, or `None`
+    /// to abort the update. Returns `Some(previous)` once the update succeeds, or `None` if
+    /// `f` aborts, in which case the current value stays in the slot. Unlike
+    /// [`AtomicPtr::fetch_update`] there is no `Err` value to hand back, since the declined
+    /// contents are still owned by the slot. See
+    /// [`fetch_update_counted`](AtomicSlot::fetch_update_counted) to also learn how often the
+    /// update was retried.
+    ///
+    /// # Safety
+    ///
+    /// The reference given to `f` is not protected against other threads: the caller must
+    /// ensure that the value stored in the slot is not freed while `f` runs, i.e. that no
+    /// other thread takes, swaps or stores (and then drops) it concurrently.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// # use std::sync::atomic::Ordering;
+    /// let slot = AtomicSlot::new(Box::new(1));
+    /// let prev = unsafe {
+    ///     slot.fetch_update(Ordering::AcqRel, Ordering::Acquire, |v| {
+    ///         v.map(|v| Box::new(v + 1))
+    ///     })
+    /// };
+    /// assert_eq!(*prev.unwrap().unwrap(), 1);
+    /// assert_eq!(*slot.take().unwrap(), 2);
+    /// ```
+    pub unsafe fn fetch_update<F>(
+        &self,
+        set_order: Ordering,
+        fetch_order: Ordering,
+        f: F,
+    ) -> Option<Option<Box<T>>>
+    where
+        F: FnMut(Option<&T>) -> Option<Box<T>>,
+        T: Sync,
+    {
+        // Safety: forwarded from the caller
+        unsafe { self.fetch_update_counted(set_order, fetch_order, f) }.0
+    }
+
+    /// Replaces the contents with the result of `f`, retrying on contention, and returns the
+    /// outcome along with how many times the update had to be retried.
+    ///
+    /// `f` is given a reference to the current value and returns the new contents, or `None`
+    /// to abort the update. If another thread changes the slot before the new value can be
+    /// installed, the new value is dropped and `f` is called again with the fresh contents.
+    ///
+    /// Returns `Some(previous)` once the update succeeds, or `None` if `f` aborted. The retry
+    /// count makes it easy to spot contended slots, for example by feeding it to a histogram.
+    ///
+    /// `set_order` is the ordering of the successful exchange and `fetch_order` that of the
+    /// loads, as with [`AtomicPtr::fetch_update`]. With debug assertions, panics if
+    /// `fetch_order` is `Release` or `AcqRel`, or stronger than `set_order`, or if `set_order`
+    /// is too weak to publish the new value.
+    ///
+    /// # Safety
+    ///
+    /// The reference given to `f` is not protected against other threads: the caller must
+    /// ensure that the value stored in the slot is not freed while `f` runs, i.e. that no
+    /// other thread takes, swaps or stores (and then drops) it concurrently.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// # use std::sync::atomic::Ordering;
+    /// let slot = AtomicSlot::new(Box::new(1));
+    /// let (prev, retries) = unsafe {
+    ///     slot.fetch_update_counted(Ordering::AcqRel, Ordering::Acquire, |v| {
+    ///         Some(Box::new(v.copied().unwrap_or(0) + 1))
+    ///     })
+    /// };
+    /// assert_eq!(*prev.unwrap().unwrap(), 1);
+    /// assert_eq!(retries, 0);
+    /// assert_eq!(*slot.take().unwrap(), 2);
+    /// ```
+    pub unsafe fn fetch_update_counted<F>(
+        &self,
+        set_order: Ordering,
+        fetch_order: Ordering,
+        mut f: F,
+    ) -> (Option<Option<Box<T>>>, u32)
+    where
+        F: FnMut(Option<&T>) -> Option<Box<T>>,
+        T: Sync,
+    {
+        debug_assert_cas_orderings(set_order, fetch_order);
+        debug_assert_publishing(set_order);
+        let mut retries = 0;
+        let mut current = self.inner.load(fetch_order);
+        loop {
+            // Safety: the caller guarantees `current` is not freed while `f` runs
+            let Some(new) = f(unsafe { current.as_ref() }) else {
+                return (None, retries);
+            };
+            let new = Box::into_raw(new);
+            match self
+                .inner
+                .compare_exchange(current, new, set_order, fetch_order)
+            {
+                // Safety: pointers in the slot come from `Box::into_raw`
+                Ok(prev) => return (Some(unsafe { into_box(prev) }), retries),
+                Err(actual) => {
+                    // Safety: `new` was never installed, so it is still uniquely owned
+                    drop(unsafe { Box::from_raw(new) });
+                    current = actual;
+                    retries += 1;
+                }
+            }
+        }
+    }
+
+    /// Swaps in `value` unless the slot still holds `last_seen`, for change detection.
+    ///
+    /// `last_seen` is the address of a value the caller observed earlier, for example
+    /// `&*boxed as *const T` before storing it. If the slot still holds that same allocation
+    /// nothing changed, so the slot is left alone and `value` is handed back in `Err`.
+    /// Otherwise `value` is installed and the old contents are returned in `Ok`.
+    ///
+    /// The comparison is by address only. If the value at `last_seen` was freed and a new
+    /// value allocated at the same address, this counts as unchanged.
+    ///
+    /// # Panics
+    ///
+    /// With debug assertions, panics if `order` is `Relaxed` or `Acquire`, which cannot
+    /// publish `value`.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// # use std::sync::atomic::Ordering;
+    /// let first = Box::new(1);
+    /// let seen = &*first as *const i32;
+    /// let slot = AtomicSlot::new(first);
+    ///
+    /// // Still the same value: nothing is swapped
+    /// let unchanged = slot.swap_unless_unchanged(seen, Box::new(2), Ordering::AcqRel);
+    /// assert_eq!(*unchanged.unwrap_err(), 2);
+    ///
+    /// // Someone else replaced it: the swap goes ahead
+    /// slot.store(Some(Box::new(3)));
+    /// let old = slot.swap_unless_unchanged(seen, Box::new(4), Ordering::AcqRel);
+    /// assert_eq!(*old.unwrap().unwrap(), 3);
+    /// ```
+    pub fn swap_unless_unchanged(
+        &self,
+        last_seen: *const T,
+        value: Box<T>,
+        order: Ordering,
+    ) -> Result<Option<Box<T>>, Box<T>> {
+        debug_assert_publishing(order);
+        let new = Box::into_raw(value);
+        let mut current = self.inner.load(Ordering::Acquire);
+        loop {
+            if ptr::eq(current, last_seen) {
+                // Safety: `new` was never installed, so it is still uniquely owned
+                return Err(unsafe { Box::from_raw(new) });
+            }
+            match self
+                .inner
+                .compare_exchange_weak(current, new, order, Ordering::Acquire)
+            {
+                // Safety: pointers in the slot come from `Box::into_raw`
+                Ok(prev) => return Ok(unsafe { into_box(prev) }),
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    /// Installs `new` if the slot holds the same allocation as `current`, comparing by
+    /// pointer identity rather than value equality.
+    ///
+    /// `current` is typically a reference into a box the caller held before, and `None`
+    /// matches an empty slot. On success the old contents are returned in `Ok`; on
+    /// failure `new` is handed back unchanged in `Err`, so nothing is leaked or dropped. As
+    /// with [`swap_unless_unchanged`](Self::swap_unless_unchanged), a freed value whose address
+    /// was reused by a new allocation compares equal.
+    ///
+    /// `current` is an `Option<&T>` rather than an `Option<&Box<T>>`: only its address is
+    /// compared, and once a box is stored in the slot the caller has no `Box` left to borrow,
+    /// only a reference to the value it saw. A caller that does hold a box for comparison
+    /// passes `Some(&**boxed)`, or `as_deref` on an `Option<Box<T>>`.
+    ///
+    /// `success` and `failure` are the orderings of the underlying
+    /// [`AtomicPtr::compare_exchange`].
+    ///
+    /// # Panics
+    ///
+    /// With debug assertions, panics if `failure` is `Release` or `AcqRel`, or stronger than
+    /// `success`.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// # use std::sync::atomic::Ordering::{AcqRel, Acquire};
+    /// let slot = AtomicSlot::empty();
+    /// let task = Box::new("task");
+    ///
+    /// // Put the task back only if nobody stored another one meanwhile
+    /// assert!(slot.compare_exchange(None, Some(task), AcqRel, Acquire).is_ok());
+    /// let rejected = slot.compare_exchange(None, Some(Box::new("other")), AcqRel, Acquire);
+    /// assert_eq!(*rejected.unwrap_err().unwrap(), "other");
+    /// ```
+    pub fn compare_exchange(
+        &self,
+        current: Option<&T>,
+        new: Option<Box<T>>,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<Option<Box<T>>, Option<Box<T>>> {
+        debug_assert_cas_orderings(success, failure);
+        if new.is_some() {
+            debug_assert_publishing(success);
+        }
+        let current = current.map_or(ptr::null_mut(), |v| ptr::from_ref(v).cast_mut());
+        let new = new.map_or(ptr::null_mut(), Box::into_raw);
+        match self.inner.compare_exchange(current, new, success, failure) {
+            // Safety: pointers in the slot come from `Box::into_raw`
+            Ok(prev) => Ok(unsafe { into_box(prev) }),
+            // Safety: `new` was never installed, so it is still uniquely owned
+            Err(_) => Err(unsafe { into_box(new) }),
+        }
+    }
+
+    /// Like [`compare_exchange`](Self::compare_exchange), but may fail spuriously even when
+    /// the slot holds `current`, which can be cheaper inside a retry loop.
+    ///
+    /// On LL/SC architectures such as ARM, the strong exchange has to loop internally to rule
+    /// out spurious failures; the weak one leaves that to the caller's own loop. Because a
+    /// failure does not prove that the slot differs from `current`, call it in a loop. As with
+    /// the strong exchange, `new` is handed back in `Err` on any failure, so retrying never
+    /// leaks or reallocates it. `current` is an `Option<&T>` rather than an `Option<&Box<T>>`
+    /// for the same reason as on the strong exchange: only its address is compared, and a
+    /// stored box leaves the caller nothing but a reference to compare with.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// # use std::sync::atomic::Ordering::{AcqRel, Acquire};
+    /// let slot = AtomicSlot::empty();
+    /// let mut value = Some(Box::new(1));
+    /// while let Err(back) = slot.compare_exchange_weak(None, value, AcqRel, Acquire) {
+    ///     value = back;
+    /// }
+    /// assert_eq!(*slot.take().unwrap(), 1);
+    /// ```
+    pub fn compare_exchange_weak(
+        &self,
+        current: Option<&T>,
+        new: Option<Box<T>>,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<Option<Box<T>>, Option<Box<T>>> {
+        debug_assert_cas_orderings(success, failure);
+        if new.is_some() {
+            debug_assert_publishing(success);
+        }
+        let current = current.map_or(ptr::null_mut(), |v| ptr::from_ref(v).cast_mut());
+        let new = new.map_or(ptr::null_mut(), Box::into_raw);
+        match self
+            .inner
+            .compare_exchange_weak(current, new, success, failure)
+        {
+            // Safety: pointers in the slot come from `Box::into_raw`
+            Ok(prev) => Ok(unsafe { into_box(prev) }),
+            // Safety: `new` was never installed, so it is still uniquely owned
+            Err(_) => Err(unsafe { into_box(new) }),
+        }
+    }
+
+    /// Swaps in `new` if `matches` accepts the current value, returning the matched value in
+    /// `Ok`; otherwise puts the current value back and returns `new` in `Err`.
+    ///
+    /// This allows arbitrary, possibly expensive, match predicates where pointer identity or
+    /// hashing does not fit. An empty slot never matches.
+    ///
+    /// Like [`swap_when`](Self::swap_when), the current value is taken out while `matches`
+    /// runs, so other threads see the slot as empty in that window. If another thread stores a
+    /// value meanwhile, that newer value wins: it replaces both the restored value and `new`,
+    /// which are then dropped.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let slot = AtomicSlot::new(Box::new(String::from("v1")));
+    /// let old = slot.compare_exchange_by(|s| s.starts_with('v'), Some(Box::new("v2".into())));
+    /// assert_eq!(*old.unwrap(), "v1");
+    /// assert!(slot.compare_exchange_by(|s| s == "v1", None).is_err());
+    /// ```
+    pub fn compare_exchange_by<P>(
+        &self,
+        matches: P,
+        new: Option<Box<T>>,
+    ) -> Result<Box<T>, Option<Box<T>>>
+    where
+        P: FnOnce(&T) -> bool,
+    {
+        let Some(current) = self.take() else {
+            return Err(new);
+        };
+        if matches(&current) {
+            if let Some(new) = new {
+                self.restore(new);
+            }
+            Ok(current)
+        } else {
+            self.restore(current);
+            Err(new)
+        }
+    }
+
+    /// Combines `incoming` with the current contents using `f` and stores the result.
+    ///
+    /// The current contents are taken out, possibly as `None`, and merged with `incoming`. If
+    /// another thread filled the slot before the result could be stored, the result becomes
+    /// the new `incoming` and is merged with that value in turn, so `f` may run several times
+    /// and must be associative and commutative, like an addition. No intermediate box is
+    /// dropped or leaked by a retry.
+    ///
+    /// Other threads see the slot as empty while a merge is in progress.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let total = AtomicSlot::new(Box::new(5));
+    /// let add = |acc: Option<Box<i32>>, mut n: Box<i32>| {
+    ///     *n += acc.map_or(0, |acc| *acc);
+    ///     n
+    /// };
+    /// total.merge(Box::new(3), add);
+    /// assert_eq!(*total.take().unwrap(), 8);
+    /// ```
+    pub fn merge<F>(&self, mut incoming: Box<T>, mut f: F)
+    where
+        F: FnMut(Option<Box<T>>, Box<T>) -> Box<T>,
+    {
+        loop {
+            let merged = f(self.take(), incoming);
+            match self.try_store(merged) {
+                Ok(()) => return,
+                Err(merged) => incoming = merged,
+            }
+        }
+    }
+
+    /// Takes the current contents, but only if `pred` accepts them.
+    ///
+    /// `pred` is given a reference to the value in place, and the value is only taken by a
+    /// compare-exchange from that value to empty. A declined value is never moved, so the slot
+    /// is left untouched. If another thread changes the slot before the exchange, `pred` is
+    /// called again with the fresh contents. Returns `None` if the slot is empty or `pred`
+    /// declines.
+    ///
+    /// # Safety
+    ///
+    /// The reference given to `pred` is not protected against other threads: the caller must
+    /// ensure that the value stored in the slot is not freed while `pred` runs, i.e. that no
+    /// other thread takes, swaps or stores (and then drops) it concurrently.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let slot = AtomicSlot::new(Box::new(3));
+    /// assert!(unsafe { slot.take_if(|v| *v > 5) }.is_none());
+    /// assert_eq!(*unsafe { slot.take_if(|v| *v == 3) }.unwrap(), 3);
+    /// ```
+    pub unsafe fn take_if(&self, mut pred: impl FnMut(&T) -> bool) -> Option<Box<T>>
+    where
+        T: Sync,
+    {
+        let mut current = self.inner.load(Ordering::Acquire);
+        loop {
+            // Safety: the caller guarantees `current` is not freed while `pred` runs
+            if !pred(unsafe { current.as_ref() }?) {
+                return None;
+            }
+            match self.inner.compare_exchange_weak(
+                current,
+                ptr::null_mut(),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                // Safety: pointers in the slot come from `Box::into_raw`
+                Ok(taken) => return unsafe { into_box(taken) },
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    /// Swaps in a value built by `make`, but only if `pred` accepts the current contents.
+    ///
+    /// Returns the old contents when the swap happened and `None` otherwise, in which case
+    /// `make` is never called and nothing is allocated.
+    ///
+    /// The crate has no reclamation scheme for borrowing a value other threads may take, so
+    /// the current contents are taken out while `pred` runs and put back if it declines.
+    /// Other threads see the slot as empty in that window, and a value they store meanwhile
+    /// replaces the one being inspected.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let cache = AtomicSlot::new(Box::new(3));
+    /// let stale = |v: Option<&i32>| v.is_none_or(|v| *v < 5);
+    /// assert_eq!(*cache.swap_when(stale, || Box::new(10)).unwrap(), 3);
+    /// assert!(cache.swap_when(stale, || unreachable!()).is_none());
+    /// ```
+    pub fn swap_when<P, F>(&self, pred: P, make: F) -> Option<Box<T>>
+    where
+        P: Fn(Option<&T>) -> bool,
+        F: FnOnce() -> Box<T>,
+    {
+        let current = self.take();
+        if pred(current.as_deref()) {
+            self.restore(make());
+            current
+        } else {
+            if let Some(current) = current {
+                self.restore(current);
+            }
+            None
+        }
+    }
+
+    /// Awaits `fut` and stores its output if the slot is empty, handing the value back if the
+    /// slot was filled in the meantime.
+    ///
+    /// Nothing is ever overwritten, so a value produced asynchronously is delivered at most
+    /// once. If the returned future is dropped before `fut` completes, nothing is stored.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let slot = AtomicSlot::empty();
+    /// assert!(slot.fill_from(async { Box::new(1) }).await.is_none());
+    /// assert_eq!(*slot.fill_from(async { Box::new(2) }).await.unwrap(), 2);
+    /// # }
+    /// ```
+    #[cfg(feature = "async")]
+    pub async fn fill_from<F>(&self, fut: F) -> Option<Box<T>>
+    where
+        F: core::future::Future<Output = Box<T>>,
+    {
+        self.try_store(fut.await).err()
+    }
+
+    /// Stores `value` into the slot, then wakes the task registered with `waker`.
+    ///
+    /// The store uses release ordering and happens before the wake, so a consumer that
+    /// registers its waker and then checks the slot either finds the value or is woken
+    /// afterwards to check again. This lets producers notify waker registries owned outside
+    /// the slot.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// use atomic_waker::AtomicWaker;
+    /// use std::future::poll_fn;
+    /// use std::task::Poll;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let (slot, waker) = (AtomicSlot::empty(), AtomicWaker::new());
+    /// slot.store_wake(Some(Box::new(1)), &waker);
+    /// let value = poll_fn(|cx| {
+    ///     waker.register(cx.waker());
+    ///     slot.take().map_or(Poll::Pending, Poll::Ready)
+    /// })
+    /// .await;
+    /// assert_eq!(*value, 1);
+    /// # }
+    /// ```
+    #[cfg(feature = "async")]
+    pub fn store_wake(&self, value: Option<Box<T>>, waker: &atomic_waker::AtomicWaker) {
+        self.store(value);
+        waker.wake();
+    }
+
+    /// Returns a mutable reference to the contents without an atomic operation.
+    ///
+    /// The `&mut self` receiver guarantees no other thread can access the slot, which is
+    /// typical in constructors and teardown code.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let mut slot = AtomicSlot::new(Box::new(1));
+    /// *slot.get_mut().unwrap() += 1;
+    /// assert_eq!(*slot.take().unwrap(), 2);
+    /// ```
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        // Safety: pointers in the slot come from `Box::into_raw`, and `&mut self` rules out
+        // any concurrent take
+        unsafe { self.ptr_mut().as_mut() }
+    }
+
+    /// Calls `f` with a mutable reference to the contents, if any, returning its result.
+    ///
+    /// A scoped form of [`get_mut`](Self::get_mut) for setup code that inspects and edits the
+    /// contents through exclusive access.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let mut slot = AtomicSlot::new(Box::new(vec![1]));
+    /// let len = slot.with_mut(|v| {
+    ///     let v = v.unwrap();
+    ///     v.push(2);
+    ///     v.len()
+    /// });
+    /// assert_eq!(len, 2);
+    /// assert!(AtomicSlot::<i32>::empty().with_mut(|v| v.is_none()));
+    /// ```
+    pub fn with_mut<R>(&mut self, f: impl FnOnce(Option<&mut T>) -> R) -> R {
+        f(self.get_mut())
+    }
+
+    /// Reads the pointer through exclusive access, without an atomic operation.
+    fn ptr_mut(&mut self) -> *mut T {
+        sync::with_ptr_mut(&mut self.inner, |ptr| *ptr)
+    }
+
+    /// Returns a mutable reference to the contents, first filling the slot with `f()` if it
+    /// is empty.
+    ///
+    /// Like [`get_mut`](Self::get_mut), the `&mut self` receiver rules out other threads, so
+    /// `f` runs at most once and no exchange is needed.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let mut slot = AtomicSlot::empty();
+    /// slot.get_or_insert_with(|| Box::new(Vec::new())).push(1);
+    /// slot.get_or_insert_with(|| unreachable!()).push(2);
+    /// assert_eq!(*slot.take().unwrap(), [1, 2]);
+    /// ```
+    pub fn get_or_insert_with(&mut self, f: impl FnOnce() -> Box<T>) -> &mut T {
+        if self.ptr_mut().is_null() {
+            self.inner.store(Box::into_raw(f()), Ordering::Relaxed);
+        }
+        // Safety: the slot is occupied, its pointer came from `Box::into_raw`, and `&mut self`
+        // rules out any concurrent take
+        unsafe { &mut *self.ptr_mut() }
+    }
+
+    /// Consumes the slot, returning its contents without an atomic operation.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let slot = AtomicSlot::new(Box::new(4));
+    /// assert_eq!(*slot.into_inner().unwrap(), 4);
+    /// ```
+    #[must_use = "the contents are dropped if unused"]
+    pub fn into_inner(self) -> Option<Box<T>> {
+        let mut this = ManuallyDrop::new(self);
+        // Safety: pointers in the slot come from `Box::into_raw`, and `this` is never dropped,
+        // so ownership moves to the returned box
+        unsafe { into_box(this.ptr_mut()) }
+    }
+
+    /// Empties the slot without dropping its contents, with the specified memory `order`.
+    ///
+    /// The value's destructor never runs and its memory is never freed, which can speed up
+    /// shutdown when the process is about to exit anyway. Unlike `Drop`, leaking is always
+    /// explicit.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// # use std::sync::atomic::Ordering;
+    /// let slot = AtomicSlot::new(Box::new(vec![1u8; 1024]));
+    /// slot.leak(Ordering::Acquire);
+    /// assert!(slot.is_none());
+    /// ```
+    pub fn leak(&self, order: Ordering) {
+        // Ownership of the pointer is abandoned on purpose
+        let _ = self.inner.swap(ptr::null_mut(), order);
+    }
+
+    /// Consumes the slot and returns its pointer, null if it was empty, without dropping
+    /// the contents.
+    ///
+    /// The caller may free the pointer with `Box::from_raw` or leave it leaked.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let slot = AtomicSlot::new(Box::new(3));
+    /// let ptr = slot.into_leaked();
+    /// assert_eq!(*unsafe { Box::from_raw(ptr) }, 3);
+    /// ```
+    pub fn into_leaked(self) -> *mut T {
+        ManuallyDrop::new(self).ptr_mut()
+    }
+
+    /// Puts back a value that was temporarily taken out of the slot.
+    ///
+    /// If another thread stored a value in the meantime, the newer value wins and `value` is
+    /// dropped, just as `store` drops whatever it replaces.
+    pub(crate) fn restore(&self, value: Box<T>) {
+        let _ = self.try_store(value);
+    }
+}
+
+/// How a [`swap_detect`](AtomicSlot::swap_detect) changed the occupancy of a slot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Transition {
+    /// The slot was empty and now holds a value.
+    EmptyToFull,
+    /// The slot held a value, which was replaced.
+    FullToFull,
+    /// The slot held a value, which was taken.
+    FullToEmpty,
+    /// The slot was empty and still is.
+    EmptyToEmpty,
+}
+
+/// Why [`AtomicSlot::try_take`] did not take a value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TakeError {
+    /// The slot was empty.
+    Empty,
+    /// Another thread changed the slot before the value could be taken.
+    Contended,
+}
+
+impl core::fmt::Display for TakeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            TakeError::Empty => "slot is empty",
+            TakeError::Contended => "slot was changed concurrently",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TakeError {}
+
+/// Checks in debug builds that an operation installing a value publishes it with release
+/// semantics, without which the reader of the pointer may see the box uninitialized.
+fn debug_assert_publishing(order: Ordering) {
+    debug_assert!(
+        matches!(
+            order,
+            Ordering::Release | Ordering::AcqRel | Ordering::SeqCst
+        ),
+        "installing a value needs Release, AcqRel or SeqCst ordering to publish it, got {order:?}"
+    );
+}
+
+/// Checks the contract of [`AtomicPtr::compare_exchange`] in debug builds: the failure
+/// ordering is a load ordering no stronger than the load part of the success ordering.
+fn debug_assert_cas_orderings(success: Ordering, failure: Ordering) {
+    fn load_strength(order: Ordering) -> u8 {
+        match order {
+            Ordering::SeqCst => 2,
+            Ordering::Acquire | Ordering::AcqRel => 1,
+            _ => 0,
+        }
+    }
+    debug_assert!(
+        !matches!(failure, Ordering::Release | Ordering::AcqRel),
+        "failure ordering cannot be Release or AcqRel"
+    );
+    debug_assert!(
+        load_strength(failure) <= load_strength(success),
+        "failure ordering cannot be stronger than success ordering"
+    );
+}
+
+/// Converts a raw pointer from the slot back into an owned box, with null as `None`.
+///
+/// # Safety
+///
+/// `ptr` must be null or come from `Box::into_raw` and not be owned elsewhere.
+unsafe fn into_box<T>(ptr: *mut T) -> Option<Box<T>> {
+    if ptr.is_null() {
+        None
+    } else {
+        // Safety: guaranteed by the caller
+        Some(unsafe { Box::from_raw(ptr) })
+    }
+}
+
+impl<T: Clone> AtomicSlot<T> {
+    /// Installs a clone of `template`, returning the old contents, or another clone of
+    /// `template` if the slot was empty, so the caller always receives a box.
+    ///
+    /// Each call clones `template` once, or twice when the slot was empty, and allocates a
+    /// box for every clone.
+    ///
+    /// Uses acquire–release ordering.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let slot: AtomicSlot<Vec<u8>> = AtomicSlot::empty();
+    /// let template = Vec::with_capacity(8);
+    /// let mut batch = slot.swap_or_clone(&template);
+    /// batch.push(1);
+    /// assert!(slot.swap_or_clone(&template).is_empty());
+    /// ```
+    pub fn swap_or_clone(&self, template: &T) -> Box<T> {
+        self.swap(Some(Box::new(template.clone())))
+            .unwrap_or_else(|| Box::new(template.clone()))
+    }
+
+    /// Returns a new slot holding a deep copy of the contents.
+    ///
+    /// This takes `&mut self` because reading the value in place is only sound while no other
+    /// thread can take and free it. For a slot shared behind `&`, take the value, clone it
+    /// and store it back instead.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let mut config = AtomicSlot::new(Box::new(vec![1, 2]));
+    /// let snapshot = config.clone_exclusive();
+    /// config.get_mut().unwrap().push(3);
+    /// assert_eq!(*snapshot.take().unwrap(), [1, 2]);
+    /// ```
+    pub fn clone_exclusive(&mut self) -> Self {
+        match self.get_mut() {
+            Some(value) => Self::new(Box::new(value.clone())),
+            None => Self::empty(),
+        }
+    }
+}
+
+impl<T: PartialEq> AtomicSlot<T> {
+    /// Compares the contents of two slots by value.
+    ///
+    /// The `&mut` receivers rule out concurrent takes, so the values can be borrowed safely.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let mut a = AtomicSlot::new(Box::new(1));
+    /// let mut b = AtomicSlot::new(Box::new(2));
+    /// assert!(a == b);
+    /// assert!(!a.eq_exclusive(&mut b));
+    /// ```
+    pub fn eq_exclusive(&mut self, other: &mut Self) -> bool {
+        self.get_mut().as_deref() == other.get_mut().as_deref()
+    }
+}
+
+impl<T: core::any::Any + Send> AtomicSlot<T> {
+    /// Takes the current contents as a type-erased `Box<dyn Any + Send>`.
+    ///
+    /// The box is unsized in place, so the allocation is reused; consumers can downcast it
+    /// back to `T`.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let slot = AtomicSlot::new(Box::new(7u16));
+    /// let erased = slot.take_erased().unwrap();
+    /// assert_eq!(*erased.downcast::<u16>().unwrap(), 7);
+    /// ```
+    pub fn take_erased(&self) -> Option<Box<dyn core::any::Any + Send>> {
+        self.take()
+            .map(|value| value as Box<dyn core::any::Any + Send>)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: core::hash::Hash> AtomicSlot<T> {
+    /// Returns the hash of `value` as computed by [`compare_hash_exchange`].
+    ///
+    /// The hash uses [`DefaultHasher`](std::hash::DefaultHasher) with its default keys, so it
+    /// is stable within a process but should not be persisted.
+    ///
+    /// [`compare_hash_exchange`]: Self::compare_hash_exchange
+    pub fn value_hash(value: &T) -> u64 {
+        use std::hash::{BuildHasher, BuildHasherDefault, DefaultHasher};
+
+        BuildHasherDefault::<DefaultHasher>::default().hash_one(value)
+    }
+
+    /// Replaces the contents with `new` if the current value hashes to `expected_hash`.
+    ///
+    /// On a match the old value is returned in `Ok`. Otherwise, or if the slot is empty, the
+    /// slot is left as it was and `new` is handed back in `Err`. Hashes are computed with
+    /// [`value_hash`](Self::value_hash).
+    ///
+    /// Two different values can have the same hash, in which case a value that merely
+    /// collides with the expected one is replaced. With a 64-bit hash this is rare, but use a
+    /// full comparison where a false positive would be harmful.
+    ///
+    /// The value is taken out of the slot while it is hashed, so concurrent observers see an
+    /// empty slot in the meantime. If another thread stores a value during that window, the
+    /// newer value wins: whatever this call would have put in the slot is dropped.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let slot = AtomicSlot::new(Box::new(String::from("v1")));
+    /// let expected = AtomicSlot::value_hash(&String::from("v1"));
+    ///
+    /// let old = slot.compare_hash_exchange(expected, Some(Box::new(String::from("v2"))));
+    /// assert_eq!(*old.unwrap(), "v1");
+    ///
+    /// let rejected = slot.compare_hash_exchange(expected, Some(Box::new(String::from("v3"))));
+    /// assert_eq!(*rejected.unwrap_err().unwrap(), "v3");
+    /// ```
+    pub fn compare_hash_exchange(
+        &self,
+        expected_hash: u64,
+        new: Option<Box<T>>,
+    ) -> Result<Box<T>, Option<Box<T>>> {
+        let Some(current) = self.take() else {
+            return Err(new);
+        };
+        if Self::value_hash(&current) == expected_hash {
+            if let Some(new) = new {
+                self.restore(new);
+            }
+            Ok(current)
+        } else {
+            self.restore(current);
+            Err(new)
+        }
+    }
+}
+
+impl AtomicSlot<()> {
+    /// Claims the slot if it is unclaimed, returning whether this call claimed it.
+    ///
+    /// This uses the slot as a lightweight one-shot mutual-exclusion flag. The claimed state
+    /// is a `Box<()>`, which for a zero-sized type is a dangling non-null pointer, so claiming
+    /// never allocates.
+    ///
+    /// Uses acquire–release ordering.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let flag = AtomicSlot::empty();
+    /// assert!(flag.try_claim());
+    /// assert!(!flag.try_claim());
+    /// flag.release_claim();
+    /// assert!(flag.try_claim());
+    /// ```
+    pub fn try_claim(&self) -> bool {
+        self.try_store(Box::new(())).is_ok()
+    }
+
+    /// Releases a claim taken with [`try_claim`](Self::try_claim).
+    ///
+    /// Uses acquire–release ordering.
+    pub fn release_claim(&self) {
+        self.store(None);
+    }
+}
+
+impl<U> AtomicSlot<Vec<U>> {
+    /// Creates a new `AtomicSlot` containing an empty `Vec` with at least `cap` capacity.
+    ///
+    /// Useful for accumulation slots where the first pushes should not reallocate.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let slot = AtomicSlot::<Vec<u8>>::new_with_capacity(16);
+    /// let vec = slot.take().unwrap();
+    /// assert!(vec.is_empty());
+    /// assert!(vec.capacity() >= 16);
+    /// ```
+    pub fn new_with_capacity(cap: usize) -> Self {
+        Self::new(Box::new(Vec::with_capacity(cap)))
+    }
+}
+
+impl AtomicSlot<String> {
+    /// Creates a new `AtomicSlot` containing an empty `String` with at least `cap` capacity.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let slot = AtomicSlot::<String>::new_with_capacity(32);
+    /// let s = slot.take().unwrap();
+    /// assert!(s.is_empty());
+    /// assert!(s.capacity() >= 32);
+    /// ```
+    pub fn new_with_capacity(cap: usize) -> Self {
+        Self::new(Box::new(String::with_capacity(cap)))
+    }
+}
+
+impl<U> AtomicSlot<Box<[U]>> {
+    /// Takes the boxed slice and splits it at `mid` into two owned slices, leaving the slot
+    /// empty.
+    ///
+    /// The prefix keeps the original allocation, shrunk to fit, and the suffix is moved into
+    /// a new one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mid > len`, like [`slice::split_at`]. The slot is left empty in that case.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let slot = AtomicSlot::new(Box::new(vec![1, 2, 3, 4].into_boxed_slice()));
+    /// let (head, tail) = slot.take_split_at(1).unwrap();
+    /// assert_eq!((&*head, &*tail), (&[1][..], &[2, 3, 4][..]));
+    /// ```
+    #[allow(
+        clippy::type_complexity,
+        reason = "a pair of boxed slices reads clearly"
+    )]
+    pub fn take_split_at(&self, mid: usize) -> Option<(Box<[U]>, Box<[U]>)> {
+        let mut prefix = self.take()?.into_vec();
+        assert!(mid <= prefix.len(), "mid > len");
+        let suffix = prefix.split_off(mid);
+        Some((prefix.into_boxed_slice(), suffix.into_boxed_slice()))
+    }
+}
+
+#[cfg(feature = "compression")]
+impl AtomicSlot<Box<[u8]>> {
+    /// Takes zlib-compressed bytes out of the slot and decompresses them.
+    ///
+    /// Returns `None` if the slot is empty. Malformed or truncated input is reported as an
+    /// [`std::io::Error`], in which case the compressed bytes are dropped.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// use flate2::{Compression, write::ZlibEncoder};
+    /// use std::io::Write;
+    ///
+    /// let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    /// encoder.write_all(b"frame").unwrap();
+    /// let slot = AtomicSlot::new(Box::new(encoder.finish().unwrap().into_boxed_slice()));
+    /// assert_eq!(slot.take_decompress().unwrap().unwrap(), b"frame");
+    /// ```
+    pub fn take_decompress(&self) -> Option<std::io::Result<Vec<u8>>> {
+        use std::io::Read;
+
+        let compressed = self.take()?;
+        let mut out = Vec::new();
+        Some(
+            flate2::read::ZlibDecoder::new(&compressed[..])
+                .read_to_end(&mut out)
+                .map(|_| out),
+        )
+    }
+}
+
+impl<T, E> AtomicSlot<Result<T, E>> {
+    /// Takes the current contents and splits the `Result` into a separately boxed `Ok` or
+    /// `Err` payload, leaving the slot empty.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let slot = AtomicSlot::<Result<u32, String>>::new(Box::new(Err("bad".into())));
+    /// assert_eq!(*slot.take_result().unwrap().unwrap_err(), "bad");
+    /// assert!(slot.take_result().is_none());
+    /// ```
+    pub fn take_result(&self) -> Option<Result<Box<T>, Box<E>>> {
+        self.take().map(|result| match *result {
+            Ok(value) => Ok(Box::new(value)),
+            Err(err) => Err(Box::new(err)),
+        })
+    }
+}
+
+/// Builder for an [`AtomicSlot`], created by [`AtomicSlot::builder`].
+///
+/// Lets the initial contents of a slot be chosen in a single expression.
+pub struct SlotInit<T> {
+    value: Option<Box<T>>,
+}
+
+impl<T> SlotInit<T> {
+    /// Sets the initial contents to `value`, replacing any previously set value.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let slot = AtomicSlot::builder().value(Box::new("ready")).build();
+    /// assert_eq!(*slot.take().unwrap(), "ready");
+    /// ```
+    pub fn value(mut self, value: Box<T>) -> Self {
+        self.value = Some(value);
+        self
+    }
+
+    /// Sets the initial contents to the result of `f` if `cond` is `true`.
+    ///
+    /// `f` is only called, and so only allocates, when `cond` is `true`.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let slot = AtomicSlot::builder().value_if(false, || Box::new(1)).build();
+    /// assert!(slot.is_none());
+    /// ```
+    pub fn value_if(self, cond: bool, f: impl FnOnce() -> Box<T>) -> Self {
+        if cond { self.value(f()) } else { self }
+    }
+
+    /// Creates the `AtomicSlot`.
+    pub fn build(self) -> AtomicSlot<T> {
+        match self.value {
+            Some(value) => AtomicSlot::new(value),
+            None => AtomicSlot::empty(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn sequential_swap_and_take() {
+        let slot = AtomicSlot::new(Box::new(10));
+
+        let old = slot.swap(Some(Box::new(20)));
+        assert_eq!(*old.unwrap(), 10);
+
+        let taken = slot.take();
+        assert_eq!(*taken.unwrap(), 20);
+        assert!(slot.is_none());
+    }
+
+    #[test]
+    fn sequential_empty_store() {
+        let slot = AtomicSlot::<i32>::empty();
+        assert!(slot.is_none());
+
+        slot.store(Some(Box::new(5)));
+        assert!(slot.is_some());
+        assert_eq!(*slot.take().unwrap(), 5);
+    }
+
+    #[test]
+    fn new_with_capacity_preallocates() {
+        let slot = AtomicSlot::<Vec<u64>>::new_with_capacity(64);
+        let vec = slot.take().unwrap();
+        assert!(vec.is_empty());
+        assert!(vec.capacity() >= 64);
+
+        let slot = AtomicSlot::<String>::new_with_capacity(10);
+        let s = slot.take().unwrap();
+        assert!(s.is_empty());
+        assert!(s.capacity() >= 10);
+    }
+
+    #[test]
+    fn take_result_destructures_payload() {
+        let slot = AtomicSlot::<Result<u8, &str>>::new(Box::new(Ok(7)));
+        assert_eq!(slot.take_result(), Some(Ok(Box::new(7))));
+        assert!(slot.is_none());
+
+        slot.store(Some(Box::new(Err("oops"))));
+        assert_eq!(slot.take_result(), Some(Err(Box::new("oops"))));
+        assert!(slot.is_none());
+        assert_eq!(slot.take_result(), None);
+    }
+
+    #[test]
+    fn swap_when_checks_predicate() {
+        let slot = AtomicSlot::new(Box::new(1));
+        assert!(
+            slot.swap_when(|v| v == Some(&2), || unreachable!())
+                .is_none()
+        );
+        assert_eq!(*slot.take().unwrap(), 1);
+
+        assert!(slot.swap_when(|v| v.is_none(), || Box::new(5)).is_none());
+        assert_eq!(
+            *slot.swap_when(|v| v == Some(&5), || Box::new(6)).unwrap(),
+            5
+        );
+        assert_eq!(*slot.take().unwrap(), 6);
+    }
+
+    #[test]
+    fn take_transmute_between_repr_c_structs() {
+        #[derive(Debug, PartialEq)]
+        #[repr(C)]
+        struct Pair {
+            lo: u32,
+            hi: u32,
+        }
+        #[derive(Debug, PartialEq)]
+        #[repr(C)]
+        struct Range {
+            start: u32,
+            end: u32,
+        }
+
+        let slot = AtomicSlot::new(Box::new(Pair { lo: 1, hi: 9 }));
+        // Safety: both structs are two `u32`s with the same layout
+        let range = unsafe { slot.take_transmute::<Range>() }.unwrap();
+        assert_eq!(*range, Range { start: 1, end: 9 });
+        assert!(slot.is_none());
+        // Safety: as above
+        assert!(unsafe { slot.take_transmute::<Range>() }.is_none());
+    }
+
+    #[test]
+    fn take_with_finalizer_runs_before_return() {
+        let slot = AtomicSlot::new(Box::new(String::from("pending")));
+        let mut ran = false;
+        let value = slot
+            .take_with_finalizer(|s| {
+                ran = true;
+                s.push_str(" flushed");
+            })
+            .unwrap();
+        assert!(ran);
+        assert_eq!(*value, "pending flushed");
+        assert!(slot.take_with_finalizer(|_| unreachable!()).is_none());
+    }
+
+    #[test]
+    fn take_erased_downcasts_back() {
+        #[derive(Debug, PartialEq)]
+        struct Payload(u64);
+
+        let boxed = Box::new(Payload(11));
+        let addr: *const Payload = &*boxed;
+        let slot = AtomicSlot::new(boxed);
+
+        let erased = slot.take_erased().unwrap();
+        assert!(!erased.is::<u64>());
+        let payload = erased.downcast::<Payload>().unwrap();
+        assert!(core::ptr::eq(&*payload, addr));
+        assert_eq!(*payload, Payload(11));
+        assert!(slot.take_erased().is_none());
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn fill_from_respects_concurrent_store() {
+        let slot = AtomicSlot::empty();
+        assert!(slot.fill_from(async { Box::new(1) }).await.is_none());
+        assert_eq!(*slot.take().unwrap(), 1);
+
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let fill = slot.fill_from(async { Box::new(rx.await.unwrap()) });
+        slot.store(Some(Box::new(2)));
+        tx.send(3).unwrap();
+        assert_eq!(*fill.await.unwrap(), 3);
+        assert_eq!(*slot.take().unwrap(), 2);
+
+        // A fill dropped before its future completes stores nothing
+        drop(slot.fill_from(async { Box::new(4) }));
+        assert!(slot.is_none());
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn store_wake_wakes_registered_task() {
+        use atomic_waker::AtomicWaker;
+        use std::sync::Arc;
+        use std::task::Poll;
+
+        let shared = Arc::new((AtomicSlot::empty(), AtomicWaker::new()));
+        let consumer = {
+            let shared = shared.clone();
+            tokio::spawn(async move {
+                core::future::poll_fn(|cx| {
+                    shared.1.register(cx.waker());
+                    shared.0.take().map_or(Poll::Pending, Poll::Ready)
+                })
+                .await
+            })
+        };
+        // Let the consumer register and go to sleep before storing
+        tokio::task::yield_now().await;
+        assert!(!consumer.is_finished());
+
+        shared.0.store_wake(Some(Box::new(5)), &shared.1);
+        assert_eq!(*consumer.await.unwrap(), 5);
+    }
+
+    #[test]
+    fn drop_frees_contents_once() {
+        use std::sync::atomic::AtomicUsize;
+
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+        struct Counted(#[allow(dead_code)] String);
+        impl Drop for Counted {
+            fn drop(&mut self) {
+                DROPS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        drop(AtomicSlot::new(Box::new(Counted(String::from("payload")))));
+        assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+
+        let slot = AtomicSlot::new(Box::new(Counted(String::new())));
+        let taken = slot.take();
+        drop(slot);
+        assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+        drop(taken);
+        assert_eq!(DROPS.load(Ordering::Relaxed), 2);
+
+        drop(AtomicSlot::<Counted>::empty());
+        assert_eq!(DROPS.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn compare_exchange_by_identity() {
+        use Ordering::{AcqRel, Acquire};
+
+        let slot = AtomicSlot::new(Box::new(1));
+        let lookalike = Box::new(1);
+        // Equal value, different allocation
+        let back = slot.compare_exchange(Some(&lookalike), None, AcqRel, Acquire);
+        assert!(back.unwrap_err().is_none());
+        assert!(slot.is_some());
+
+        // The slot was not empty, so `new` comes back untouched
+        let back = slot.compare_exchange(None, Some(Box::new(2)), AcqRel, Acquire);
+        assert_eq!(*back.unwrap_err().unwrap(), 2);
+
+        let first = slot.take().unwrap();
+        let seen = &*first as *const i32;
+        assert!(
+            slot.compare_exchange(None, Some(first), AcqRel, Acquire)
+                .unwrap()
+                .is_none()
+        );
+        // Safety: nothing takes the value out of the slot while this reference is in use
+        let current = unsafe { &*seen };
+        let mut new = Some(Box::new(3));
+        let old = loop {
+            match slot.compare_exchange_weak(Some(current), new, AcqRel, Acquire) {
+                Ok(old) => break old.unwrap(),
+                Err(back) => new = back,
+            }
+        };
+        assert!(core::ptr::eq(&*old, seen));
+        assert_eq!(*slot.take().unwrap(), 3);
+    }
+
+    #[test]
+    fn take_split_at_halves_slice() {
+        let slot = AtomicSlot::new(Box::new((0..6).collect::<Box<[u32]>>()));
+        let (head, tail) = slot.take_split_at(2).unwrap();
+        assert_eq!(*head, [0, 1]);
+        assert_eq!(*tail, [2, 3, 4, 5]);
+        assert!(slot.take_split_at(0).is_none());
+
+        slot.store(Some(Box::new(Box::new([7u32, 8]) as Box<[u32]>)));
+        let (head, tail) = slot.take_split_at(2).unwrap();
+        assert_eq!(*head, [7, 8]);
+        assert!(tail.is_empty());
+    }
+
+    #[test]
+    #[should_panic = "mid > len"]
+    fn take_split_at_past_end_panics() {
+        let slot = AtomicSlot::new(Box::new(Box::new([1u8]) as Box<[u8]>));
+        let _ = slot.take_split_at(2);
+    }
+
+    #[test]
+    fn try_store_race_has_one_winner() {
+        let slot = Arc::new(AtomicSlot::empty());
+        let barrier = Arc::new(std::sync::Barrier::new(8));
+        let threads: Vec<_> = (0..8)
+            .map(|i| {
+                let (slot, barrier) = (slot.clone(), barrier.clone());
+                thread::spawn(move || {
+                    barrier.wait();
+                    match slot.try_store(Box::new(i)) {
+                        Ok(()) => Some(i),
+                        Err(back) => {
+                            assert_eq!(*back, i);
+                            None
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        let winners: Vec<_> = threads
+            .into_iter()
+            .filter_map(|th| th.join().unwrap())
+            .collect();
+        assert_eq!(winners.len(), 1);
+        assert_eq!(*slot.take().unwrap(), winners[0]);
+    }
+
+    #[test]
+    fn compare_exchange_by_branches() {
+        let slot = AtomicSlot::new(Box::new(10));
+        let back = slot.compare_exchange_by(|v| *v > 10, Some(Box::new(20)));
+        assert_eq!(*back.unwrap_err().unwrap(), 20);
+        assert_eq!(*slot.take().unwrap(), 10);
+
+        assert!(
+            slot.compare_exchange_by(|_| true, None)
+                .unwrap_err()
+                .is_none()
+        );
+
+        slot.store(Some(Box::new(11)));
+        let old = slot.compare_exchange_by(|v| *v > 10, Some(Box::new(21)));
+        assert_eq!(*old.unwrap(), 11);
+        assert_eq!(*slot.take().unwrap(), 21);
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn take_decompress_round_trips() {
+        use flate2::{Compression, write::ZlibEncoder};
+        use std::io::Write;
+
+        let original: Vec<u8> = (0..4096u32).map(|i| (i % 251) as u8).collect();
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&original).unwrap();
+        let compressed = encoder.finish().unwrap();
+        assert!(compressed.len() < original.len());
+
+        let slot = AtomicSlot::new(Box::new(compressed.into_boxed_slice()));
+        assert_eq!(slot.take_decompress().unwrap().unwrap(), original);
+        assert!(slot.take_decompress().is_none());
+
+        slot.store(Some(Box::new(Box::new(*b"not zlib") as Box<[u8]>)));
+        assert!(slot.take_decompress().unwrap().is_err());
+        assert!(slot.is_none());
+    }
+
+    #[test]
+    fn into_inner_moves_value_out_once() {
+        let drops = Arc::new(());
+        let slot = AtomicSlot::new(Box::new(drops.clone()));
+        assert_eq!(Arc::strong_count(&drops), 2);
+
+        let inner = slot.into_inner().unwrap();
+        assert_eq!(Arc::strong_count(&drops), 2);
+        drop(inner);
+        assert_eq!(Arc::strong_count(&drops), 1);
+        assert!(AtomicSlot::<u8>::empty().into_inner().is_none());
+    }
+
+    #[test]
+    fn get_mut_edits_in_place() {
+        let mut slot = AtomicSlot::new(Box::new(vec![1, 2]));
+        slot.get_mut().unwrap().push(3);
+        assert_eq!(*slot.take().unwrap(), [1, 2, 3]);
+        assert!(slot.get_mut().is_none());
+    }
+
+    #[test]
+    fn debug_shows_occupancy_only() {
+        let slot = AtomicSlot::<String>::empty();
+        assert_eq!(format!("{slot:?}"), "AtomicSlot { occupied: false }");
+        slot.store(Some(Box::new(String::from("secret"))));
+        assert_eq!(format!("{slot:?}"), "AtomicSlot { occupied: true }");
+        assert!(!format!("{slot:#?}").contains("secret"));
+    }
+
+    #[test]
+    fn take_route_returns_key_and_value() {
+        #[derive(Debug, PartialEq)]
+        enum Route {
+            Short,
+            Long,
+        }
+
+        let slot = AtomicSlot::new(Box::new(String::from("a longer message")));
+        let classify = |s: &String| {
+            if s.len() > 8 {
+                Route::Long
+            } else {
+                Route::Short
+            }
+        };
+        let (route, msg) = slot.take_route(classify).unwrap();
+        assert_eq!(route, Route::Long);
+        assert_eq!(*msg, "a longer message");
+        assert!(slot.is_none());
+        assert!(slot.take_route(classify).is_none());
+    }
+
+    #[test]
+    fn merge_accumulates() {
+        let slot = AtomicSlot::empty();
+        let concat = |acc: Option<Box<String>>, s: Box<String>| match acc {
+            Some(mut acc) => {
+                acc.push_str(&s);
+                acc
+            }
+            None => s,
+        };
+        slot.merge(Box::new(String::from("a")), concat);
+        slot.merge(Box::new(String::from("b")), concat);
+        assert_eq!(*slot.take().unwrap(), "ab");
+    }
+
+    #[test]
+    fn clone_exclusive_copies_contents() {
+        let mut filled = AtomicSlot::new(Box::new(String::from("config")));
+        let copy = filled.clone_exclusive();
+        assert_eq!(*copy.take().unwrap(), "config");
+        assert_eq!(*filled.take().unwrap(), "config");
+
+        let mut empty = AtomicSlot::<String>::empty();
+        assert!(empty.clone_exclusive().is_none());
+    }
+
+    #[test]
+    fn take_with_ctx_passes_value_and_context() {
+        let prefix = String::from("log: ");
+        let slot = AtomicSlot::new(Box::new("started"));
+        let line = slot.take_with_ctx(&prefix, |msg, prefix| format!("{prefix}{msg}"));
+        assert_eq!(line.as_deref(), Some("log: started"));
+        assert!(slot.is_none());
+        assert_eq!(
+            slot.take_with_ctx(&prefix, |_, _| unreachable!()),
+            None::<()>
+        );
+    }
+
+    #[test]
+    fn from_conversions() {
+        let slot: AtomicSlot<_> = Box::new(5).into();
+        assert_eq!(*slot.take().unwrap(), 5);
+
+        let slot: AtomicSlot<_> = Some(Box::new("x")).into();
+        assert_eq!(*slot.take().unwrap(), "x");
+
+        let slot: AtomicSlot<u8> = None.into();
+        assert!(slot.is_none());
+    }
+
+    #[cfg(feature = "portable-atomic")]
+    #[test]
+    fn portable_atomic_backs_the_slot() {
+        const {
+            assert!(size_of::<AtomicSlot<u8>>() == size_of::<portable_atomic::AtomicPtr<u8>>());
+        };
+        let slot = AtomicSlot::new(Box::new(1));
+        assert_eq!(slot.try_store(Box::new(2)).map_err(|v| *v), Err(2));
+        assert_eq!(*slot.swap(Some(Box::new(3))).unwrap(), 1);
+        assert_eq!(*slot.take().unwrap(), 3);
+        assert!(slot.try_store(Box::new(4)).is_ok());
+    }
+
+    #[test]
+    fn take_if_leaves_rejected_value() {
+        let slot = AtomicSlot::new(Box::new(String::from("pending")));
+        let before = slot.as_ptr(Ordering::Relaxed);
+        assert!(unsafe { slot.take_if(|s| s == "done") }.is_none());
+        // The declined value was never moved out
+        assert_eq!(slot.as_ptr(Ordering::Relaxed), before);
+        assert_eq!(
+            *unsafe { slot.take_if(|s| s == "pending") }.unwrap(),
+            "pending"
+        );
+        assert!(unsafe { slot.take_if(|_| true) }.is_none());
+    }
+
+    #[test]
+    fn eq_by_occupancy_and_by_value() {
+        let mut a: AtomicSlot<i32> = AtomicSlot::empty();
+        let mut b = AtomicSlot::empty();
+        assert_eq!(a, b);
+        assert!(a.eq_exclusive(&mut b));
+
+        a.store(Some(Box::new(1)));
+        assert_ne!(a, b);
+        assert!(!a.eq_exclusive(&mut b));
+
+        b.store(Some(Box::new(1)));
+        assert_eq!(a, b);
+        assert!(a.eq_exclusive(&mut b));
+        b.store(Some(Box::new(2)));
+        assert_eq!(a, b);
+        assert!(!a.eq_exclusive(&mut b));
+    }
+
+    #[test]
+    fn raw_pointers_round_trip() {
+        let tracker = Arc::new(());
+        let ptr = Box::into_raw(Box::new(tracker.clone()));
+        let slot = unsafe { AtomicSlot::from_raw(ptr) };
+        assert_eq!(slot.as_ptr(Ordering::Acquire), ptr);
+
+        let taken = unsafe { slot.take_raw(Ordering::AcqRel) };
+        assert_eq!(taken, ptr);
+        assert!(unsafe { slot.take_raw(Ordering::AcqRel) }.is_null());
+        assert_eq!(Arc::strong_count(&tracker), 2);
+
+        drop(unsafe { AtomicSlot::from_raw(taken) });
+        assert_eq!(Arc::strong_count(&tracker), 1);
+        assert!(unsafe { AtomicSlot::<i32>::from_raw(ptr::null_mut()) }.is_none());
+    }
+
+    #[test]
+    fn from_raw_opt_null_and_owned() {
+        let slot = unsafe { AtomicSlot::<String>::from_raw_opt(ptr::null_mut()) };
+        assert!(slot.is_none());
+        drop(slot);
+
+        let tracker = Arc::new(());
+        let slot = unsafe { AtomicSlot::from_raw_opt(Box::into_raw(Box::new(tracker.clone()))) };
+        assert!(slot.is_some());
+        drop(slot);
+        assert_eq!(Arc::strong_count(&tracker), 1);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "cannot be Release or AcqRel")]
+    fn release_failure_ordering_panics() {
+        let slot = AtomicSlot::empty();
+        let _ = slot.try_store_ordered(Box::new(1), Ordering::AcqRel, Ordering::Release);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "needs Release, AcqRel or SeqCst ordering")]
+    fn relaxed_publishing_store_panics() {
+        let slot = AtomicSlot::empty();
+        slot.store_ordered(Some(Box::new(1)), Ordering::Relaxed);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "needs Release, AcqRel or SeqCst ordering")]
+    fn relaxed_swap_unless_unchanged_panics() {
+        let slot = AtomicSlot::empty();
+        let _ = slot.swap_unless_unchanged(ptr::null(), Box::new(1), Ordering::Relaxed);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "needs Release, AcqRel or SeqCst ordering")]
+    fn acquire_fetch_update_panics() {
+        let slot = AtomicSlot::<i32>::empty();
+        let _ = unsafe {
+            slot.fetch_update(Ordering::Acquire, Ordering::Acquire, |_| Some(Box::new(1)))
+        };
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "cannot be stronger")]
+    fn stronger_failure_ordering_panics() {
+        let slot = AtomicSlot::<i32>::empty();
+        let _ = slot.compare_exchange(None, None, Ordering::Release, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn iterating_empty_and_full_slots() {
+        assert_eq!(AtomicSlot::<i32>::empty().into_iter().count(), 0);
+        let values: Vec<_> = AtomicSlot::new(Box::new(5)).into_iter().collect();
+        assert_eq!(values, [Box::new(5)]);
+
+        let slot = AtomicSlot::new(Box::new(6));
+        let mut drain = slot.drain();
+        assert!(slot.is_none());
+        assert_eq!(*drain.next().unwrap(), 6);
+        assert!(drain.next().is_none());
+        assert_eq!(slot.drain().count(), 0);
+    }
+
+    #[test]
+    fn try_take_reports_empty() {
+        let slot = AtomicSlot::<i32>::empty();
+        assert_eq!(slot.try_take().unwrap_err(), TakeError::Empty);
+        slot.store(Some(Box::new(1)));
+        assert_eq!(*slot.try_take().unwrap(), 1);
+        assert_eq!(slot.try_take().unwrap_err(), TakeError::Empty);
+        assert_eq!(
+            TakeError::Contended.to_string(),
+            "slot was changed concurrently"
+        );
+    }
+
+    #[test]
+    fn hash_by_occupancy() {
+        use std::hash::{BuildHasher, RandomState};
+
+        let hasher = RandomState::new();
+        let empty = hasher.hash_one(AtomicSlot::<i32>::empty());
+        assert_eq!(empty, hasher.hash_one(AtomicSlot::<i32>::empty()));
+        let full = hasher.hash_one(AtomicSlot::new(Box::new(1)));
+        assert_eq!(full, hasher.hash_one(AtomicSlot::new(Box::new(2))));
+        assert_ne!(empty, full);
+    }
+
+    #[test]
+    fn leaking_skips_destructors() {
+        use std::sync::atomic::AtomicUsize;
+
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+        // A zero-sized value, so leaking it does not actually leak memory
+        struct Noisy;
+        impl Drop for Noisy {
+            fn drop(&mut self) {
+                DROPS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let slot = AtomicSlot::new(Box::new(Noisy));
+        slot.leak(Ordering::AcqRel);
+        assert!(slot.is_none());
+        slot.store(Some(Box::new(Noisy)));
+        assert!(!slot.into_leaked().is_null());
+        assert!(AtomicSlot::<Noisy>::empty().into_leaked().is_null());
+        assert_eq!(DROPS.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn swap_detect_reports_every_transition() {
+        let slot = AtomicSlot::empty();
+        let order = Ordering::AcqRel;
+        let (prev, t) = slot.swap_detect(None, order);
+        assert_eq!((prev, t), (None, Transition::EmptyToEmpty));
+        let (prev, t) = slot.swap_detect(Some(Box::new(1)), order);
+        assert_eq!((prev, t), (None, Transition::EmptyToFull));
+        let (prev, t) = slot.swap_detect(Some(Box::new(2)), order);
+        assert_eq!((prev, t), (Some(Box::new(1)), Transition::FullToFull));
+        let (prev, t) = slot.swap_detect(None, order);
+        assert_eq!((prev, t), (Some(Box::new(2)), Transition::FullToEmpty));
+        assert!(slot.is_none());
+    }
+
+    #[test]
+    fn get_or_insert_with_initializes_once() {
+        let mut calls = 0;
+        let mut slot = AtomicSlot::empty();
+        *slot.get_or_insert_with(|| {
+            calls += 1;
+            Box::new(1)
+        }) += 10;
+        *slot.get_or_insert_with(|| {
+            calls += 1;
+            Box::new(100)
+        }) += 10;
+        assert_eq!(calls, 1);
+        assert_eq!(*slot.take().unwrap(), 21);
+    }
+
+    #[test]
+    fn with_mut_changes_persist() {
+        let mut slot = AtomicSlot::new(Box::new(String::from("a")));
+        let was_some = slot.with_mut(|v| match v {
+            Some(v) => {
+                v.push('b');
+                true
+            }
+            None => false,
+        });
+        assert!(was_some);
+        assert_eq!(*slot.take().unwrap(), "ab");
+        slot.with_mut(|v| assert!(v.is_none()));
+    }
+
+    #[test]
+    fn zero_sized_values_drop_once() {
+        use std::sync::atomic::AtomicUsize;
+
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+        struct Token;
+        impl Drop for Token {
+            fn drop(&mut self) {
+                DROPS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let slot = AtomicSlot::new(Box::new(Token));
+        assert_eq!(
+            slot.as_ptr(Ordering::Relaxed),
+            ptr::NonNull::dangling().as_ptr()
+        );
+        let taken = slot.take().unwrap();
+        assert!(slot.is_none());
+        assert_eq!(DROPS.load(Ordering::Relaxed), 0);
+        drop(taken);
+        assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+
+        slot.store(Some(Box::new(Token)));
+        drop(slot.swap(Some(Box::new(Token))));
+        assert_eq!(DROPS.load(Ordering::Relaxed), 2);
+        drop(slot);
+        assert_eq!(DROPS.load(Ordering::Relaxed), 3);
+    }
+
+    // The next few tests only exercise the raw pointer round trips on one thread, so they
+    // stay cheap under `cargo +nightly miri test`
+    #[test]
+    fn new_take_drop_round_trips() {
+        for i in 0..16 {
+            let slot = AtomicSlot::new(Box::new(format!("value {i}")));
+            assert_eq!(*slot.take().unwrap(), format!("value {i}"));
+            assert!(slot.take().is_none());
+
+            // Dropped while full
+            let _ = AtomicSlot::new(Box::new(vec![i; i]));
+        }
+    }
+
+    #[test]
+    fn swap_chain_hands_back_each_value() {
+        let slot = AtomicSlot::empty();
+        assert!(slot.swap(Some(Box::new(String::from("0")))).is_none());
+        for i in 1..16 {
+            let prev = slot.swap(Some(Box::new(i.to_string()))).unwrap();
+            assert_eq!(*prev, (i - 1).to_string());
+        }
+        assert_eq!(*slot.swap(None).unwrap(), "15");
+        assert!(slot.swap(None).is_none());
+    }
+
+    #[test]
+    fn into_inner_after_swaps() {
+        let slot = AtomicSlot::new(Box::new(vec![1]));
+        drop(slot.swap(Some(Box::new(vec![2, 2]))));
+        assert_eq!(*slot.into_inner().unwrap(), [2, 2]);
+
+        let slot = AtomicSlot::new(Box::new(vec![3]));
+        drop(slot.take());
+        assert!(slot.into_inner().is_none());
+    }
+
+    #[test]
+    fn destructor_runs_exactly_once() {
+        use std::sync::atomic::AtomicUsize;
+
+        struct Counted(Arc<AtomicUsize>);
+        impl Drop for Counted {
+            fn drop(&mut self) {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let drops = Arc::new(AtomicUsize::new(0));
+        let counted = || Box::new(Counted(drops.clone()));
+
+        // take: the caller owns the value, the slot no longer does
+        let slot = AtomicSlot::new(counted());
+        let taken = slot.take();
+        drop(slot);
+        assert_eq!(drops.load(Ordering::Relaxed), 0);
+        drop(taken);
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+
+        // store: overwriting drops the old value, and only that one
+        let slot = AtomicSlot::new(counted());
+        slot.store(Some(counted()));
+        assert_eq!(drops.load(Ordering::Relaxed), 2);
+        slot.store(None);
+        assert_eq!(drops.load(Ordering::Relaxed), 3);
+        drop(slot);
+        assert_eq!(drops.load(Ordering::Relaxed), 3);
+
+        // slot drop: a full slot drops its value
+        drop(AtomicSlot::new(counted()));
+        assert_eq!(drops.load(Ordering::Relaxed), 4);
+
+        // into_inner: moved out without being dropped by the slot
+        let inner = AtomicSlot::new(counted()).into_inner();
+        assert_eq!(drops.load(Ordering::Relaxed), 4);
+        drop(inner);
+        assert_eq!(drops.load(Ordering::Relaxed), 5);
+    }
+
+    #[test]
+    fn take_with_size_reports_size_of() {
+        let slot = AtomicSlot::new(Box::new((1u64, 2u32)));
+        let (value, size) = slot.take_with_size().unwrap();
+        assert_eq!(*value, (1, 2));
+        assert_eq!(size, size_of::<(u64, u32)>());
+        assert!(slot.take_with_size().is_none());
+    }
+
+    #[test]
+    fn zeroed_memory_is_empty_slot() {
+        let mut pool = [const { MaybeUninit::<AtomicSlot<u32>>::zeroed() }; 8];
+        for mem in &mut pool {
+            let slot = unsafe { AtomicSlot::from_zeroed(mem) };
+            assert!(slot.is_none());
+            assert!(slot.take().is_none());
+            slot.store(Some(Box::new(4)));
+            assert_eq!(*slot.take().unwrap(), 4);
+        }
+    }
+
+    #[test]
+    fn swap_or_clone_installs_template() {
+        let template = String::from("template");
+        let slot = AtomicSlot::new(Box::new(String::from("old")));
+
+        assert_eq!(*slot.swap_or_clone(&template), "old");
+        assert_eq!(*slot.swap_or_clone(&template), "template");
+
+        slot.store(None);
+        assert_eq!(*slot.swap_or_clone(&template), "template");
+        assert_eq!(*slot.take().unwrap(), "template");
+    }
+
+    #[test]
+    fn compare_hash_exchange_matching_hash() {
+        let slot = AtomicSlot::new(Box::new(vec![1, 2, 3]));
+        let expected = AtomicSlot::value_hash(&vec![1, 2, 3]);
+
+        let old = slot.compare_hash_exchange(expected, Some(Box::new(vec![4])));
+        assert_eq!(*old.unwrap(), [1, 2, 3]);
+        assert_eq!(*slot.take().unwrap(), [4]);
+    }
+
+    #[test]
+    fn compare_hash_exchange_mismatching_hash() {
+        let slot = AtomicSlot::new(Box::new(vec![1, 2, 3]));
+        let expected = AtomicSlot::value_hash(&vec![9]);
+
+        let new = slot.compare_hash_exchange(expected, Some(Box::new(vec![4])));
+        assert_eq!(*new.unwrap_err().unwrap(), [4]);
+        assert_eq!(*slot.take().unwrap(), [1, 2, 3]);
+
+        // An empty slot never matches
+        assert!(
+            slot.compare_hash_exchange(expected, None)
+                .unwrap_err()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn fetch_update_installs_or_aborts() {
+        let slot = AtomicSlot::new(Box::new(10));
+        let aborted = unsafe {
+            slot.fetch_update(Ordering::AcqRel, Ordering::Acquire, |v| {
+                v.filter(|&&v| v > 10).map(|v| Box::new(v - 1))
+            })
+        };
+        assert!(aborted.is_none());
+        assert_eq!(*slot.take().unwrap(), 10);
+
+        let prev = unsafe {
+            slot.fetch_update(Ordering::AcqRel, Ordering::Acquire, |v| {
+                Some(Box::new(v.copied().unwrap_or(0) + 1))
+            })
+        };
+        assert!(matches!(prev, Some(None)));
+        assert_eq!(*slot.take().unwrap(), 1);
+    }
+
+    #[test]
+    fn fetch_update_counted_without_contention() {
+        let slot = AtomicSlot::new(Box::new(5));
+        let (prev, retries) = unsafe {
+            slot.fetch_update_counted(Ordering::AcqRel, Ordering::Acquire, |v| {
+                Some(Box::new(*v.unwrap() * 2))
+            })
+        };
+        assert_eq!(*prev.unwrap().unwrap(), 5);
+        assert_eq!(retries, 0);
+
+        let (aborted, retries) =
+            unsafe { slot.fetch_update_counted(Ordering::AcqRel, Ordering::Acquire, |_| None) };
+        assert!(aborted.is_none());
+        assert_eq!(retries, 0);
+        assert_eq!(*slot.take().unwrap(), 10);
+    }
+
+    #[test]
+    fn fetch_update_counted_retries_under_contention() {
+        let slot = AtomicSlot::empty();
+        let mut calls = 0;
+        let (prev, retries) = unsafe {
+            slot.fetch_update_counted(Ordering::AcqRel, Ordering::Acquire, |v| {
+                calls += 1;
+                if v.is_none() {
+                    // Simulate another thread filling the slot between the load and the CAS
+                    slot.store(Some(Box::new(100)));
+                }
+                Some(Box::new(v.copied().unwrap_or(0) + 1))
+            })
+        };
+        assert_eq!(calls, 2);
+        assert_eq!(retries, 1);
+        assert_eq!(*prev.unwrap().unwrap(), 100);
+        assert_eq!(*slot.take().unwrap(), 101);
+    }
+
+    #[test]
+    fn swap_unless_unchanged_skips_same_value() {
+        let first = Box::new(String::from("a"));
+        let seen = &*first as *const String;
+        let slot = AtomicSlot::new(first);
+
+        let rejected =
+            slot.swap_unless_unchanged(seen, Box::new(String::from("b")), Ordering::AcqRel);
+        assert_eq!(*rejected.unwrap_err(), "b");
+        assert_eq!(*slot.take().unwrap(), "a");
+    }
+
+    #[test]
+    fn swap_unless_unchanged_swaps_changed_value() {
+        let slot = AtomicSlot::empty();
+        let rejected = slot.swap_unless_unchanged(ptr::null(), Box::new(1), Ordering::AcqRel);
+        // An empty slot matches a null `last_seen`
+        assert_eq!(*rejected.unwrap_err(), 1);
+
+        let other = Box::new(0);
+        let seen = &*other as *const i32;
+        slot.store(Some(Box::new(2)));
+        let old = slot.swap_unless_unchanged(seen, Box::new(3), Ordering::AcqRel);
+        assert_eq!(*old.unwrap().unwrap(), 2);
+        assert_eq!(*slot.take().unwrap(), 3);
+    }
+
+    #[test]
+    fn take_verified_checks_checksum() {
+        let len = |v: &String| v.len() as u64;
+
+        let slot = AtomicSlot::new(Box::new(String::from("four")));
+        assert_eq!(*slot.take_verified(4, len).unwrap().unwrap(), "four");
+        assert!(slot.is_none());
+
+        slot.store(Some(Box::new(String::from("torn"))));
+        assert_eq!(*slot.take_verified(5, len).unwrap().unwrap_err(), "torn");
+        assert!(slot.is_none());
+    }
+
+    #[test]
+    fn layout_matches_atomic_ptr() {
+        const {
+            assert!(size_of::<AtomicSlot<u8>>() == size_of::<AtomicPtr<u8>>());
+            assert!(align_of::<AtomicSlot<u8>>() == align_of::<AtomicPtr<u8>>());
+            assert!(size_of::<AtomicSlot<[u64; 16]>>() == size_of::<AtomicPtr<[u64; 16]>>());
+        }
+
+        let slot = AtomicSlot::new(Box::new(12));
+        // Safety: `AtomicSlot` is `repr(transparent)` over `AtomicPtr`
+        let ptr = unsafe { &*(&slot as *const AtomicSlot<i32> as *const AtomicPtr<i32>) };
+        assert_eq!(unsafe { *ptr.load(Ordering::Acquire) }, 12);
+    }
+
+    #[test]
+    fn manual_cas_through_atomic_ptr() {
+        let slot = AtomicSlot::new(Box::new(1));
+        let atomic = unsafe { slot.as_atomic_ptr() };
+
+        // Move the value out and a replacement in with one exchange, as a stack push would
+        let old = atomic.load(Ordering::Acquire);
+        let new = Box::into_raw(Box::new(2));
+        assert!(
+            atomic
+                .compare_exchange(old, new, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok()
+        );
+        assert_eq!(*unsafe { Box::from_raw(old) }, 1);
+        assert_eq!(*slot.take().unwrap(), 2);
+
+        let slot = unsafe { AtomicSlot::<i32>::from_atomic_ptr(AtomicPtr::new(ptr::null_mut())) };
+        assert!(slot.is_none());
+        let tracker = Arc::new(());
+        let ptr = AtomicPtr::new(Box::into_raw(Box::new(tracker.clone())));
+        drop(unsafe { AtomicSlot::from_atomic_ptr(ptr) });
+        assert_eq!(Arc::strong_count(&tracker), 1);
+    }
+
+    #[test]
+    fn with_raw_ownership_round_trip() {
+        let slot = AtomicSlot::new(Box::new(String::from("from rust")));
+
+        // Free the lent value and hand back a replacement
+        let replace = |ptr| {
+            let old = unsafe { Box::from_raw(ptr) };
+            let new = Box::new(format!("{old}, via C"));
+            let len = new.len();
+            (Box::into_raw(new), len)
+        };
+        let len = unsafe { slot.with_raw_ownership(replace) };
+        assert_eq!(len, 16);
+        assert_eq!(*slot.take().unwrap(), "from rust, via C");
+
+        // An empty slot lends null, and returning null leaves it empty
+        let saw_null = unsafe { slot.with_raw_ownership(|ptr| (ptr, ptr.is_null())) };
+        assert!(saw_null);
+        assert!(slot.is_none());
+    }
+
+    #[test]
+    fn builder_value_if_true() {
+        let slot = AtomicSlot::builder()
+            .value_if(true, || Box::new(11))
+            .build();
+        assert_eq!(*slot.take().unwrap(), 11);
+    }
+
+    #[test]
+    fn builder_value_if_false() {
+        let mut called = false;
+        let slot: AtomicSlot<i32> = AtomicSlot::builder()
+            .value_if(false, || {
+                called = true;
+                Box::new(11)
+            })
+            .build();
+        assert!(!called);
+        assert!(slot.is_none());
+    }
+
+    #[test]
+    fn send_and_sync_follow_t_send() {
+        fn assert_send_sync<S: Send + Sync>() {}
+        // `Cell` is `Send` but not `Sync`, which is enough to move it through a slot
+        assert_send_sync::<AtomicSlot<std::cell::Cell<u8>>>();
     }
 
     /// Verify that AtomicSlot<T> is Send by moving it into a thread.
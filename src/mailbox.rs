@@ -0,0 +1,95 @@
+//! A single-message mailbox that counts messages lost to overwrites.
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+
+use crate::AtomicSlot;
+use crate::sync::atomic::{AtomicUsize, Ordering};
+
+/// A single-slot mailbox built on [`AtomicSlot`], where a new message replaces an unread one.
+///
+/// Every message replaced before it was received is counted, so a consumer can tell how far
+/// it fell behind the producers.
+///
+/// ```
+/// # use atomic_slot::AtomicMailbox;
+/// let mailbox = AtomicMailbox::new();
+/// mailbox.send(Box::new("first"));
+/// mailbox.send(Box::new("second"));
+/// assert_eq!(*mailbox.recv().unwrap(), "second");
+/// assert_eq!(mailbox.dropped_count(), 1);
+/// ```
+pub struct AtomicMailbox<T> {
+    slot: AtomicSlot<T>,
+    dropped: AtomicUsize,
+}
+
+impl<T> Default for AtomicMailbox<T> {
+    /// Creates an empty `AtomicMailbox<T>`.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> AtomicMailbox<T> {
+    loom_const_fn! {
+        /// Creates an empty `AtomicMailbox`.
+        pub const fn new() -> Self {
+            Self {
+                slot: AtomicSlot::empty(),
+                dropped: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    /// Delivers `msg`, dropping an unread message if there is one and counting it in
+    /// [`dropped_count`](Self::dropped_count).
+    ///
+    /// Uses acquire–release ordering.
+    pub fn send(&self, msg: Box<T>) {
+        if self.slot.swap(Some(msg)).is_some() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Takes the pending message, if any.
+    ///
+    /// Uses acquire–release ordering.
+    pub fn recv(&self) -> Option<Box<T>> {
+        self.slot.take()
+    }
+
+    /// Returns the number of messages overwritten before they were received.
+    ///
+    /// The count is updated after the overwriting swap, so it may briefly lag behind a
+    /// concurrent [`send`](Self::send).
+    pub fn dropped_count(&self) -> usize {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Returns `true` if a message is waiting. Uses acquire ordering.
+    pub fn has_message(&self) -> bool {
+        self.slot.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overwritten_message_is_counted() {
+        let mailbox = AtomicMailbox::new();
+        mailbox.send(Box::new(1));
+        mailbox.send(Box::new(2));
+        assert_eq!(mailbox.dropped_count(), 1);
+        assert_eq!(*mailbox.recv().unwrap(), 2);
+        assert!(mailbox.recv().is_none());
+
+        // A received message is not a dropped one
+        mailbox.send(Box::new(3));
+        assert!(mailbox.has_message());
+        assert_eq!(*mailbox.recv().unwrap(), 3);
+        assert_eq!(mailbox.dropped_count(), 1);
+    }
+}
@@ -0,0 +1,902 @@
+//! A slot whose consumers can wait for a value instead of polling.
+
+use std::future::Future;
+use std::pin::Pin;
+#[cfg(any(feature = "async", feature = "tokio"))]
+use std::sync::Arc;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+use crate::AtomicSlot;
+use crate::sync::atomic::{AtomicUsize, Ordering};
+use crate::sync::spin_loop;
+
+/// A slot like [`AtomicSlot`] that wakes waiting consumers when a value is stored.
+///
+/// Storing and taking are still a single atomic operation when nobody is waiting. Consumers
+/// that wait register themselves first, and producers only take the lock to wake them when a
+/// registration is visible. Both sides use sequentially consistent ordering for that
+/// handshake, so a value stored while a consumer is about to wait is never missed.
+///
+/// Threads wait on a condition variable, and async tasks wait through
+/// [`take_async`](NotifySlot::take_async), whose wakers are kept under the same lock. An event
+/// loop can register a waker of its own with [`set_on_fill`](NotifySlot::set_on_fill).
+/// Tasks that want to observe every store and take, without consuming the value, wait through
+/// [`changed`](NotifySlot::changed), which has a separate count so that taking stays a single
+/// atomic operation while nobody watches.
+pub struct NotifySlot<T> {
+    slot: AtomicSlot<T>,
+    /// The registered async wakers; the mutex also backs `filled`.
+    lock: Mutex<Wakers>,
+    filled: Condvar,
+    waiters: AtomicUsize,
+    /// The number of pending `ChangedFuture`s.
+    watchers: AtomicUsize,
+}
+
+/// The wakers registered with a [`NotifySlot`].
+#[derive(Default)]
+struct Wakers {
+    /// The waker from `set_on_fill`, which counts as a waiter until it fires.
+    on_fill: Option<Waker>,
+    /// The wakers of pending `TakeFuture`s, keyed by registration and woken by the next store.
+    tasks: Vec<(usize, Waker)>,
+    /// The wakers of pending `ChangedFuture`s, keyed by registration and woken by the next
+    /// store or take.
+    changes: Vec<(usize, Waker)>,
+    next_id: usize,
+}
+
+impl<T> Default for NotifySlot<T> {
+    /// Creates an empty `NotifySlot<T>`.
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<T> NotifySlot<T> {
+    /// Creates a new `NotifySlot` containing `value`.
+    pub fn new(value: Box<T>) -> Self {
+        Self::with_slot(AtomicSlot::new(value))
+    }
+
+    /// Creates an empty `NotifySlot`.
+    pub fn empty() -> Self {
+        Self::with_slot(AtomicSlot::empty())
+    }
+
+    fn with_slot(slot: AtomicSlot<T>) -> Self {
+        Self {
+            slot,
+            lock: Mutex::new(Wakers::default()),
+            filled: Condvar::new(),
+            waiters: AtomicUsize::new(0),
+            watchers: AtomicUsize::new(0),
+        }
+    }
+
+    /// Atomically swaps out the current contents for `value`, returning the old contents.
+    ///
+    /// Wakes waiting consumers if `value` is `Some`, and pending [`changed`](NotifySlot::changed)
+    /// futures if the slot was or is now full.
+    pub fn swap(&self, value: Option<Box<T>>) -> Option<Box<T>> {
+        let filled = value.is_some();
+        let prev = self.slot.swap_ordered(value, Ordering::SeqCst);
+        if filled && self.waiters.load(Ordering::SeqCst) != 0 {
+            self.notify();
+        }
+        if filled || prev.is_some() {
+            self.announce_change();
+        }
+        prev
+    }
+
+    /// Stores `value` into the slot, dropping whatever was there before.
+    ///
+    /// Wakes waiting consumers if `value` is `Some`.
+    pub fn store(&self, value: Option<Box<T>>) {
+        let _ = self.swap(value);
+    }
+
+    /// Takes the current contents without waiting, leaving the slot empty.
+    ///
+    /// Wakes pending [`changed`](NotifySlot::changed) futures if a value was taken.
+    pub fn take(&self) -> Option<Box<T>> {
+        self.take_ordered(Ordering::AcqRel)
+    }
+
+    fn take_ordered(&self, order: Ordering) -> Option<Box<T>> {
+        let value = self.slot.take_ordered(order);
+        if value.is_some() {
+            self.announce_change();
+        }
+        value
+    }
+
+    /// Returns `true` if the slot currently contains a value.
+    pub fn is_some(&self) -> bool {
+        self.slot.is_some()
+    }
+
+    /// Takes a value, spinning for up to `spin_limit` attempts before blocking until one is
+    /// stored.
+    ///
+    /// Spinning keeps latency low when the value is about to arrive; blocking avoids burning
+    /// CPU on long waits.
+    ///
+    /// ```
+    /// # use atomic_slot::NotifySlot;
+    /// use std::sync::Arc;
+    ///
+    /// let slot = Arc::new(NotifySlot::empty());
+    /// let producer = {
+    ///     let slot = slot.clone();
+    ///     std::thread::spawn(move || slot.store(Some(Box::new(3))))
+    /// };
+    /// assert_eq!(*slot.take_adaptive(100), 3);
+    /// producer.join().unwrap();
+    /// ```
+    pub fn take_adaptive(&self, spin_limit: usize) -> Box<T> {
+        for _ in 0..spin_limit {
+            if let Some(value) = self.take() {
+                return value;
+            }
+            spin_loop();
+        }
+        self.take_blocking()
+    }
+
+    /// Takes a value, blocking the calling thread until one is stored.
+    ///
+    /// ```
+    /// # use atomic_slot::NotifySlot;
+    /// use std::sync::Arc;
+    ///
+    /// let slot = Arc::new(NotifySlot::empty());
+    /// let producer = {
+    ///     let slot = slot.clone();
+    ///     std::thread::spawn(move || slot.store(Some(Box::new(3))))
+    /// };
+    /// assert_eq!(*slot.take_blocking(), 3);
+    /// producer.join().unwrap();
+    /// ```
+    pub fn take_blocking(&self) -> Box<T> {
+        self.wait_take(None).expect("waited without a deadline")
+    }
+
+    /// Takes a value, blocking the calling thread for at most `dur` until one is stored.
+    ///
+    /// Returns `None` if the slot is still empty once `dur` has elapsed.
+    ///
+    /// ```
+    /// # use atomic_slot::NotifySlot;
+    /// use std::time::Duration;
+    ///
+    /// let slot = NotifySlot::<i32>::empty();
+    /// assert!(slot.take_timeout(Duration::from_millis(1)).is_none());
+    /// slot.store(Some(Box::new(1)));
+    /// assert_eq!(*slot.take_timeout(Duration::ZERO).unwrap(), 1);
+    /// ```
+    pub fn take_timeout(&self, dur: Duration) -> Option<Box<T>> {
+        // A duration too large to represent as a deadline is as good as waiting forever
+        self.wait_take(Instant::now().checked_add(dur))
+    }
+
+    /// Blocks until a value can be taken or `deadline` has passed.
+    ///
+    /// The slot is re-checked after every wakeup, so spurious wakeups only cost a retry.
+    fn wait_take(&self, deadline: Option<Instant>) -> Option<Box<T>> {
+        self.waiters.fetch_add(1, Ordering::SeqCst);
+        let mut guard = self.lock();
+        let value = loop {
+            if let Some(value) = self.take_ordered(Ordering::SeqCst) {
+                break Some(value);
+            }
+            match deadline {
+                None => guard = self.filled.wait(guard).unwrap_or_else(|e| e.into_inner()),
+                Some(deadline) => {
+                    let Some(remaining) = deadline.checked_duration_since(Instant::now()) else {
+                        break None;
+                    };
+                    guard = self
+                        .filled
+                        .wait_timeout(guard, remaining)
+                        .unwrap_or_else(|e| e.into_inner())
+                        .0;
+                }
+            }
+        };
+        drop(guard);
+        self.waiters.fetch_sub(1, Ordering::Relaxed);
+        value
+    }
+
+    /// Returns a future that resolves to the next value taken from the slot.
+    ///
+    /// Completes immediately if the slot is already full. Otherwise the task's waker is
+    /// registered and woken by the next store. Any number of tasks can wait at once: a store
+    /// wakes all of them, one takes the value and the others register again. Dropping a
+    /// pending future unregisters its waker.
+    ///
+    /// ```
+    /// # use atomic_slot::NotifySlot;
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let slot = NotifySlot::new(Box::new(1));
+    /// assert_eq!(*slot.take_async().await, 1);
+    /// # }
+    /// ```
+    pub fn take_async(&self) -> TakeFuture<'_, T> {
+        TakeFuture {
+            slot: self,
+            registration: None,
+        }
+    }
+
+    /// Registers `waker` to be woken once, the next time a value is stored.
+    ///
+    /// This is the notification behind [`take_async`](NotifySlot::take_async), for event
+    /// loops that poll the slot themselves. The slot keeps one such waker, separate from those
+    /// of pending `TakeFuture`s. A later `set_on_fill` replaces it, waking the replaced waker
+    /// unless it wakes the same task as the new one, so no registration is lost silently. If
+    /// the slot is already full, or is filled while registering, `waker` is woken right away.
+    ///
+    /// ```
+    /// # use atomic_slot::NotifySlot;
+    /// use std::sync::Arc;
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    /// use std::task::{Wake, Waker};
+    ///
+    /// struct Flag(AtomicBool);
+    /// impl Wake for Flag {
+    ///     fn wake(self: Arc<Self>) {
+    ///         self.0.store(true, Ordering::Release);
+    ///     }
+    /// }
+    ///
+    /// let flag = Arc::new(Flag(AtomicBool::new(false)));
+    /// let slot = NotifySlot::empty();
+    /// slot.set_on_fill(Waker::from(flag.clone()));
+    /// slot.store(Some(Box::new(1)));
+    /// assert!(flag.0.load(Ordering::Acquire));
+    /// ```
+    pub fn set_on_fill(&self, waker: Waker) {
+        let replaced = {
+            let mut wakers = self.lock();
+            let replaced = wakers.on_fill.replace(waker);
+            if replaced.is_none() {
+                self.waiters.fetch_add(1, Ordering::SeqCst);
+            }
+            let waker = wakers.on_fill.as_ref().expect("just registered");
+            replaced.filter(|replaced| !replaced.will_wake(waker))
+        };
+        // Its owner may still be waiting on it, so let it check the slot again
+        if let Some(replaced) = replaced {
+            replaced.wake();
+        }
+        // A store that missed the registration above is visible now
+        if !self.slot.is_none_ordered(Ordering::SeqCst) {
+            self.notify();
+        }
+    }
+
+    /// Returns a future that resolves the next time a value is stored into or taken from the
+    /// slot, to the pointer the slot holds at that point.
+    ///
+    /// The value is left in place, so this lets a task await any mutation without consuming
+    /// it. Changes are only counted from the first poll on, and the returned pointer is a
+    /// point-in-time identity: by the time the task runs, the slot may have changed again and
+    /// the pointed-to value may have been freed, so it is only good for comparisons. A null
+    /// pointer means the slot was empty.
+    ///
+    /// ```
+    /// # use atomic_slot::NotifySlot;
+    /// use std::sync::Arc;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let slot = Arc::new(NotifySlot::<i32>::empty());
+    /// let watcher = {
+    ///     let slot = slot.clone();
+    ///     tokio::spawn(async move { slot.changed().await.is_null() })
+    /// };
+    /// tokio::task::yield_now().await;
+    /// slot.store(Some(Box::new(1)));
+    /// assert!(!watcher.await.unwrap());
+    /// # }
+    /// ```
+    pub fn changed(&self) -> ChangedFuture<'_, T> {
+        ChangedFuture {
+            slot: self,
+            registration: None,
+        }
+    }
+
+    /// Forwards every value stored into the slot to `tx`, in the order it is taken, until the
+    /// channel closes.
+    ///
+    /// This bridges a slot-based producer into a consumer reading a tokio channel. Each round
+    /// first reserves capacity in the channel, so a full channel holds values back in the slot
+    /// rather than taking them early, and then waits for a value as
+    /// [`take_async`](NotifySlot::take_async) does. While it waits for capacity, a stored
+    /// value stays in the slot, where a later store replaces it as usual.
+    ///
+    /// The future owns a reference to the slot, so the slot outlives it; it completes once
+    /// every receiver is dropped, even while waiting for a value, leaving any value stored
+    /// after that in the slot.
+    ///
+    /// ```
+    /// # use atomic_slot::NotifySlot;
+    /// use std::sync::Arc;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let slot = Arc::new(NotifySlot::empty());
+    /// let (tx, mut rx) = tokio::sync::mpsc::channel(1);
+    /// tokio::spawn(slot.clone().forward_to(tx));
+    /// slot.store(Some(Box::new(1)));
+    /// assert_eq!(*rx.recv().await.unwrap(), 1);
+    /// # }
+    /// ```
+    #[cfg(feature = "tokio")]
+    pub async fn forward_to(self: Arc<Self>, tx: tokio::sync::mpsc::Sender<Box<T>>) {
+        loop {
+            let Ok(permit) = tx.reserve().await else {
+                return;
+            };
+            let mut take = std::pin::pin!(self.take_async());
+            let mut closed = std::pin::pin!(tx.closed());
+            let value = std::future::poll_fn(|cx| match take.as_mut().poll(cx) {
+                Poll::Ready(value) => Poll::Ready(Some(value)),
+                Poll::Pending => closed.as_mut().poll(cx).map(|()| None),
+            })
+            .await;
+            match value {
+                Some(value) => permit.send(value),
+                None => return,
+            }
+        }
+    }
+
+    /// Returns a stream of the slot's transitions between empty and full, for observers such as
+    /// a gauge that tracks whether a handoff is pending.
+    ///
+    /// The stream is driven by the same wakeups as [`changed`](NotifySlot::changed), and
+    /// coalesces them: each time it is polled after a change it compares the slot's occupancy
+    /// with the last one it reported, yielding only if it differs. A value stored and taken
+    /// again before the stream is polled therefore yields nothing, and any number of changes
+    /// between polls yield at most one item, the occupancy at the time of the poll. The first
+    /// item is a transition away from the occupancy when the stream was created. The stream
+    /// holds on to the slot and never ends.
+    ///
+    /// ```
+    /// # use atomic_slot::{NotifySlot, Occupancy};
+    /// use futures_core::Stream;
+    /// use std::pin::Pin;
+    /// use std::sync::Arc;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let slot = Arc::new(NotifySlot::empty());
+    /// let mut changes = slot.clone().occupancy_changes();
+    /// slot.store(Some(Box::new(1)));
+    /// let next = std::future::poll_fn(|cx| Pin::new(&mut changes).poll_next(cx)).await;
+    /// assert_eq!(next, Some(Occupancy::Occupied));
+    /// # }
+    /// ```
+    #[cfg(feature = "async")]
+    pub fn occupancy_changes(self: Arc<Self>) -> OccupancyChanges<T> {
+        let last = Occupancy::of(self.is_some());
+        OccupancyChanges {
+            slot: self,
+            last,
+            registration: None,
+        }
+    }
+
+    /// Registers `cx`'s waker for the next change, returning `Ready` once a change happened
+    /// since the registration was made.
+    ///
+    /// `registration` is cleared when the change is reported, so the following poll registers
+    /// again.
+    fn poll_change(&self, registration: &mut Option<usize>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut wakers = self.lock();
+        let Some(id) = *registration else {
+            self.watchers.fetch_add(1, Ordering::SeqCst);
+            let id = wakers.next_id;
+            wakers.next_id = id.wrapping_add(1);
+            wakers.changes.push((id, cx.waker().clone()));
+            *registration = Some(id);
+            return Poll::Pending;
+        };
+        // A change since the last poll woke and removed the previous waker
+        match wakers.changes.iter_mut().find(|(key, _)| *key == id) {
+            Some((_, waker)) => {
+                waker.clone_from(cx.waker());
+                Poll::Pending
+            }
+            None => {
+                *registration = None;
+                self.watchers.fetch_sub(1, Ordering::Relaxed);
+                Poll::Ready(())
+            }
+        }
+    }
+
+    /// Drops a registration made by `poll_change` that no longer needs to be reported.
+    fn unwatch(&self, registration: &mut Option<usize>) {
+        let Some(id) = registration.take() else {
+            return;
+        };
+        let mut wakers = self.lock();
+        // The waker may already have been woken and removed, but the count is held either way
+        wakers.changes.retain(|(key, _)| *key != id);
+        self.watchers.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Wakes every pending `changed` future.
+    fn announce_change(&self) {
+        if self.watchers.load(Ordering::SeqCst) == 0 {
+            return;
+        }
+        let changes = core::mem::take(&mut self.lock().changes);
+        for (_, waker) in changes {
+            waker.wake();
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Wakers> {
+        self.lock.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Wakes every waiting consumer.
+    ///
+    /// Taking the lock ensures a consumer cannot miss the value between checking the slot and
+    /// starting to wait.
+    fn notify(&self) {
+        let (on_fill, tasks) = {
+            let mut wakers = self.lock();
+            let on_fill = wakers.on_fill.take();
+            if on_fill.is_some() {
+                self.waiters.fetch_sub(1, Ordering::Relaxed);
+            }
+            (on_fill, core::mem::take(&mut wakers.tasks))
+        };
+        self.filled.notify_all();
+        for (_, waker) in tasks {
+            waker.wake();
+        }
+        if let Some(waker) = on_fill {
+            waker.wake();
+        }
+    }
+}
+
+/// A future that takes the next value from a [`NotifySlot`], created by
+/// [`NotifySlot::take_async`].
+#[must_use = "futures do nothing unless polled"]
+pub struct TakeFuture<'a, T> {
+    slot: &'a NotifySlot<T>,
+    /// The key of this future's waker in the slot, once the future had to wait.
+    registration: Option<usize>,
+}
+
+impl<T> Future for TakeFuture<'_, T> {
+    type Output = Box<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Box<T>> {
+        let this = self.get_mut();
+        if let Some(value) = this.slot.take() {
+            this.unregister();
+            return Poll::Ready(value);
+        }
+        {
+            let mut wakers = this.slot.lock();
+            let id = *this.registration.get_or_insert_with(|| {
+                this.slot.waiters.fetch_add(1, Ordering::SeqCst);
+                let id = wakers.next_id;
+                wakers.next_id = id.wrapping_add(1);
+                id
+            });
+            // A store since the last poll woke and removed the previous waker
+            match wakers.tasks.iter_mut().find(|(key, _)| *key == id) {
+                Some((_, waker)) => waker.clone_from(cx.waker()),
+                None => wakers.tasks.push((id, cx.waker().clone())),
+            }
+        }
+        // A store that missed the registration above is visible now
+        match this.slot.take_ordered(Ordering::SeqCst) {
+            Some(value) => {
+                this.unregister();
+                Poll::Ready(value)
+            }
+            None => Poll::Pending,
+        }
+    }
+}
+
+impl<T> TakeFuture<'_, T> {
+    fn unregister(&mut self) {
+        let Some(id) = self.registration.take() else {
+            return;
+        };
+        self.slot.lock().tasks.retain(|(key, _)| *key != id);
+        self.slot.waiters.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl<T> Drop for TakeFuture<'_, T> {
+    fn drop(&mut self) {
+        self.unregister();
+    }
+}
+
+/// A future that resolves on the next change to a [`NotifySlot`], created by
+/// [`NotifySlot::changed`].
+#[must_use = "futures do nothing unless polled"]
+pub struct ChangedFuture<'a, T> {
+    slot: &'a NotifySlot<T>,
+    /// The key of this future's waker in the slot, once it has been polled.
+    registration: Option<usize>,
+}
+
+impl<T> Future for ChangedFuture<'_, T> {
+    type Output = *const T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<*const T> {
+        let this = self.get_mut();
+        this.slot
+            .poll_change(&mut this.registration, cx)
+            .map(|()| this.slot.slot.as_ptr(Ordering::Acquire).cast_const())
+    }
+}
+
+impl<T> Drop for ChangedFuture<'_, T> {
+    fn drop(&mut self) {
+        self.slot.unwatch(&mut self.registration);
+    }
+}
+
+/// Whether a slot holds a value, as reported by [`NotifySlot::occupancy_changes`].
+#[cfg(feature = "async")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Occupancy {
+    /// The slot is empty.
+    Empty,
+    /// The slot holds a value.
+    Occupied,
+}
+
+#[cfg(feature = "async")]
+impl Occupancy {
+    fn of(is_some: bool) -> Self {
+        if is_some {
+            Occupancy::Occupied
+        } else {
+            Occupancy::Empty
+        }
+    }
+}
+
+/// A stream of a [`NotifySlot`]'s occupancy transitions, created by
+/// [`NotifySlot::occupancy_changes`].
+#[cfg(feature = "async")]
+#[must_use = "streams do nothing unless polled"]
+pub struct OccupancyChanges<T> {
+    slot: Arc<NotifySlot<T>>,
+    /// The occupancy last yielded, or the initial one.
+    last: Occupancy,
+    registration: Option<usize>,
+}
+
+#[cfg(feature = "async")]
+impl<T> futures_core::Stream for OccupancyChanges<T> {
+    type Item = Occupancy;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Occupancy>> {
+        let this = self.get_mut();
+        loop {
+            let changed = this.slot.poll_change(&mut this.registration, cx).is_ready();
+            // Checked after registering, so a change made while no registration was held, such
+            // as right after the previous item, is not lost
+            let now = Occupancy::of(this.slot.is_some());
+            if now != this.last {
+                this.slot.unwatch(&mut this.registration);
+                this.last = now;
+                return Poll::Ready(Some(now));
+            }
+            if !changed {
+                return Poll::Pending;
+            }
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T> Drop for OccupancyChanges<T> {
+    fn drop(&mut self) {
+        self.slot.unwatch(&mut self.registration);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn take_adaptive_value_during_spin() {
+        let slot = NotifySlot::new(Box::new(1));
+        assert_eq!(*slot.take_adaptive(1), 1);
+
+        let slot = Arc::new(NotifySlot::empty());
+        let producer = {
+            let slot = slot.clone();
+            thread::spawn(move || slot.store(Some(Box::new(2))))
+        };
+        assert_eq!(*slot.take_adaptive(usize::MAX), 2);
+        producer.join().unwrap();
+    }
+
+    // Polls with real sleeps until the other thread parks, which is far too slow under Miri
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn take_adaptive_parks_after_spin() {
+        let slot = Arc::new(NotifySlot::empty());
+        let consumer = {
+            let slot = slot.clone();
+            thread::spawn(move || *slot.take_adaptive(0))
+        };
+        while slot.waiters.load(Ordering::SeqCst) == 0 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        slot.store(Some(Box::new(5)));
+        assert_eq!(consumer.join().unwrap(), 5);
+        assert!(!slot.is_some());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn take_blocking_waits_for_store() {
+        let slot = Arc::new(NotifySlot::empty());
+        let consumer = {
+            let slot = slot.clone();
+            thread::spawn(move || *slot.take_blocking())
+        };
+        while slot.waiters.load(Ordering::SeqCst) == 0 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        slot.store(Some(Box::new(7)));
+        assert_eq!(consumer.join().unwrap(), 7);
+        assert_eq!(slot.waiters.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn take_timeout_expires_or_receives() {
+        let slot = Arc::new(NotifySlot::<i32>::empty());
+        assert!(slot.take_timeout(Duration::from_millis(10)).is_none());
+        assert_eq!(slot.waiters.load(Ordering::SeqCst), 0);
+
+        let consumer = {
+            let slot = slot.clone();
+            thread::spawn(move || slot.take_timeout(Duration::from_secs(60)).map(|v| *v))
+        };
+        while slot.waiters.load(Ordering::SeqCst) == 0 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        slot.store(Some(Box::new(8)));
+        assert_eq!(consumer.join().unwrap(), Some(8));
+    }
+
+    #[test]
+    fn on_fill_waker_fires_once() {
+        use std::sync::atomic::AtomicUsize;
+        use std::task::Wake;
+
+        struct Counter(AtomicUsize);
+        impl Wake for Counter {
+            fn wake(self: Arc<Self>) {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let first = Arc::new(Counter(AtomicUsize::new(0)));
+        let second = Arc::new(Counter(AtomicUsize::new(0)));
+        let slot = NotifySlot::empty();
+        slot.set_on_fill(Waker::from(first.clone()));
+        // Re-registering the same waker does not wake it
+        slot.set_on_fill(Waker::from(first.clone()));
+        assert_eq!(first.0.load(Ordering::Relaxed), 0);
+
+        // A different waker displaces it, and the displaced one is woken
+        slot.set_on_fill(Waker::from(second.clone()));
+        assert_eq!(Arc::strong_count(&first), 1);
+        assert_eq!(first.0.load(Ordering::Relaxed), 1);
+        assert_eq!(slot.waiters.load(Ordering::SeqCst), 1);
+
+        slot.store(Some(Box::new(1)));
+        slot.store(Some(Box::new(2)));
+        assert_eq!(first.0.load(Ordering::Relaxed), 1);
+        assert_eq!(second.0.load(Ordering::Relaxed), 1);
+        assert_eq!(slot.waiters.load(Ordering::SeqCst), 0);
+
+        // Already full: woken right away
+        slot.set_on_fill(Waker::from(first.clone()));
+        assert_eq!(first.0.load(Ordering::Relaxed), 2);
+    }
+
+    // The tokio runtime's IO and time drivers are not supported by Miri
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn take_async_waits_for_store() {
+        let slot = Arc::new(NotifySlot::empty());
+        let consumer = {
+            let slot = slot.clone();
+            tokio::spawn(async move { *slot.take_async().await })
+        };
+        tokio::task::yield_now().await;
+        assert_eq!(slot.waiters.load(Ordering::SeqCst), 1);
+
+        let producer = {
+            let slot = slot.clone();
+            thread::spawn(move || slot.store(Some(Box::new(9))))
+        };
+        assert_eq!(consumer.await.unwrap(), 9);
+        producer.join().unwrap();
+        assert_eq!(slot.waiters.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn dropped_take_future_unregisters() {
+        let slot = NotifySlot::<u8>::empty();
+        let timeout = Duration::from_millis(10);
+        assert!(
+            tokio::time::timeout(timeout, slot.take_async())
+                .await
+                .is_err()
+        );
+        assert_eq!(slot.waiters.load(Ordering::SeqCst), 0);
+        assert!(slot.lock().tasks.is_empty());
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn every_waiting_task_is_woken() {
+        let slot = Arc::new(NotifySlot::empty());
+        let consumers: Vec<_> = (0..2)
+            .map(|_| {
+                let slot = slot.clone();
+                tokio::spawn(async move { *slot.take_async().await })
+            })
+            .collect();
+        tokio::task::yield_now().await;
+        assert_eq!(slot.waiters.load(Ordering::SeqCst), 2);
+
+        // The first store wakes both tasks; whichever loses the race waits for the second
+        slot.store(Some(Box::new(1)));
+        tokio::task::yield_now().await;
+        slot.store(Some(Box::new(2)));
+
+        // A task whose waker was lost would never finish
+        let mut taken = Vec::new();
+        for consumer in consumers {
+            let joined = tokio::time::timeout(Duration::from_secs(10), consumer).await;
+            taken.push(joined.expect("a waiting task was never woken").unwrap());
+        }
+        taken.sort();
+        assert_eq!(taken, [1, 2]);
+        assert_eq!(slot.waiters.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn changed_fires_on_store_and_take() {
+        let slot = Arc::new(NotifySlot::empty());
+        let watcher = {
+            let slot = slot.clone();
+            tokio::spawn(async move { slot.changed().await as usize })
+        };
+        tokio::task::yield_now().await;
+        assert_eq!(slot.watchers.load(Ordering::SeqCst), 1);
+
+        let producer = {
+            let slot = slot.clone();
+            tokio::spawn(async move { slot.store(Some(Box::new(4))) })
+        };
+        producer.await.unwrap();
+        let stored = slot.slot.as_ptr(Ordering::Acquire) as usize;
+        assert_eq!(watcher.await.unwrap(), stored);
+        assert_eq!(slot.watchers.load(Ordering::SeqCst), 0);
+
+        // Taking is a change too, and leaves the slot empty
+        let watcher = {
+            let slot = slot.clone();
+            tokio::spawn(async move { slot.changed().await.is_null() })
+        };
+        tokio::task::yield_now().await;
+        assert_eq!(*slot.take().unwrap(), 4);
+        assert!(watcher.await.unwrap());
+
+        // A dropped future gives its registration back
+        let timeout = Duration::from_millis(10);
+        assert!(tokio::time::timeout(timeout, slot.changed()).await.is_err());
+        assert_eq!(slot.watchers.load(Ordering::SeqCst), 0);
+        assert!(slot.lock().changes.is_empty());
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn occupancy_changes_follow_store_and_take() {
+        use futures_core::Stream;
+
+        fn next<S: Stream + Unpin>(stream: &mut S) -> impl Future<Output = Option<S::Item>> {
+            std::future::poll_fn(|cx| Pin::new(&mut *stream).poll_next(cx))
+        }
+
+        let slot = Arc::new(NotifySlot::empty());
+        let mut changes = slot.clone().occupancy_changes();
+
+        slot.store(Some(Box::new(1)));
+        assert_eq!(next(&mut changes).await, Some(Occupancy::Occupied));
+        slot.take();
+        assert_eq!(next(&mut changes).await, Some(Occupancy::Empty));
+
+        // Refilling and draining between polls is coalesced away
+        slot.store(Some(Box::new(2)));
+        slot.take();
+        let timeout = Duration::from_millis(10);
+        assert!(
+            tokio::time::timeout(timeout, next(&mut changes))
+                .await
+                .is_err()
+        );
+
+        // Several changes between polls yield the occupancy at the time of the poll
+        slot.store(Some(Box::new(3)));
+        slot.store(Some(Box::new(4)));
+        assert_eq!(next(&mut changes).await, Some(Occupancy::Occupied));
+        drop(changes);
+        assert_eq!(slot.watchers.load(Ordering::SeqCst), 0);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn forward_to_sends_values_in_order() {
+        let slot = Arc::new(NotifySlot::empty());
+        let (tx, mut rx) = tokio::sync::mpsc::channel(1);
+        let forwarder = tokio::spawn(slot.clone().forward_to(tx));
+
+        for i in 0..5 {
+            slot.store(Some(Box::new(i)));
+            assert_eq!(*rx.recv().await.unwrap(), i);
+        }
+
+        // With the channel full, the next value waits in the slot
+        slot.store(Some(Box::new(5)));
+        while slot.is_some() {
+            tokio::task::yield_now().await;
+        }
+        slot.store(Some(Box::new(6)));
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+        assert!(slot.is_some());
+        assert_eq!(*rx.recv().await.unwrap(), 5);
+        assert_eq!(*rx.recv().await.unwrap(), 6);
+
+        // Dropping the receiver ends the forwarder while it waits for a value
+        drop(rx);
+        let timeout = Duration::from_secs(10);
+        tokio::time::timeout(timeout, forwarder)
+            .await
+            .expect("the forwarder outlived its channel")
+            .unwrap();
+        assert_eq!(slot.waiters.load(Ordering::SeqCst), 0);
+    }
+}
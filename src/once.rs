@@ -0,0 +1,139 @@
+//! A slot that is filled at most once and then lends out shared references.
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+
+use core::marker::PhantomData;
+use core::ptr;
+
+use crate::sync::atomic::{AtomicPtr, Ordering};
+use crate::sync::with_ptr_mut;
+
+/// A lazily initialized cell backed by a single atomic pointer.
+///
+/// Unlike [`AtomicSlot`](crate::AtomicSlot), a `OnceSlot` hands out `&T` references to its
+/// value, so the value must stay installed while they are live. `OnceSlot` enforces this by
+/// only offering [`take`](OnceSlot::take) through `&mut self`, which the borrow checker
+/// forbids while any reference from [`get_or_init`](OnceSlot::get_or_init) exists.
+pub struct OnceSlot<T> {
+    inner: AtomicPtr<T>,
+    _phantom: PhantomData<Option<Box<T>>>,
+}
+
+// Safety: the value may be dropped on another thread, and `&T` is shared across threads
+unsafe impl<T: Send> Send for OnceSlot<T> {}
+unsafe impl<T: Send + Sync> Sync for OnceSlot<T> {}
+
+impl<T> Default for OnceSlot<T> {
+    /// Creates an empty `OnceSlot<T>`.
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<T> OnceSlot<T> {
+    loom_const_fn! {
+        /// Creates an empty `OnceSlot`.
+        pub const fn empty() -> Self {
+            Self {
+                inner: AtomicPtr::new(ptr::null_mut()),
+                _phantom: PhantomData,
+            }
+        }
+    }
+
+    /// Returns the value, or `None` if the slot was not initialized yet. Uses acquire
+    /// ordering.
+    pub fn get(&self) -> Option<&T> {
+        // Safety: an installed value is only freed through `&mut self`
+        unsafe { self.inner.load(Ordering::Acquire).as_ref() }
+    }
+
+    /// Returns the value, initializing it with `f` if the slot is empty.
+    ///
+    /// If several threads race to initialize the slot, each may run `f`, but only one box is
+    /// installed; the others are dropped and every caller gets the installed value.
+    ///
+    /// ```
+    /// # use atomic_slot::OnceSlot;
+    /// let cache = OnceSlot::empty();
+    /// assert_eq!(cache.get_or_init(|| Box::new(1)), &1);
+    /// assert_eq!(cache.get_or_init(|| Box::new(2)), &1);
+    /// ```
+    pub fn get_or_init(&self, f: impl FnOnce() -> Box<T>) -> &T {
+        if let Some(value) = self.get() {
+            return value;
+        }
+        let new = Box::into_raw(f());
+        match self
+            .inner
+            .compare_exchange(ptr::null_mut(), new, Ordering::AcqRel, Ordering::Acquire)
+        {
+            // Safety: `new` is installed and only freed through `&mut self`
+            Ok(_) => unsafe { &*new },
+            Err(winner) => {
+                // Safety: `new` was never installed, so it is still uniquely owned
+                drop(unsafe { Box::from_raw(new) });
+                // Safety: as above, the winner stays installed for the borrow of `self`
+                unsafe { &*winner }
+            }
+        }
+    }
+
+    /// Takes the value out, leaving the slot empty so it can be initialized again.
+    ///
+    /// Requires `&mut self`, so no reference handed out by `get_or_init` can still be alive.
+    ///
+    /// ```
+    /// # use atomic_slot::OnceSlot;
+    /// let mut cache = OnceSlot::empty();
+    /// cache.get_or_init(|| Box::new(3));
+    /// assert_eq!(*cache.take().unwrap(), 3);
+    /// assert!(cache.get().is_none());
+    /// ```
+    pub fn take(&mut self) -> Option<Box<T>> {
+        let ptr = with_ptr_mut(&mut self.inner, |ptr| {
+            core::mem::replace(ptr, ptr::null_mut())
+        });
+        if ptr.is_null() {
+            None
+        } else {
+            // Safety: non-null pointers in the slot come from `Box::into_raw`
+            Some(unsafe { Box::from_raw(ptr) })
+        }
+    }
+}
+
+impl<T> Drop for OnceSlot<T> {
+    fn drop(&mut self) {
+        drop(self.take());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+
+    #[test]
+    fn racing_initializers_agree() {
+        let slot = Arc::new(OnceSlot::empty());
+        let barrier = Arc::new(Barrier::new(4));
+        let threads: Vec<_> = (0..4)
+            .map(|i| {
+                let (slot, barrier) = (slot.clone(), barrier.clone());
+                thread::spawn(move || {
+                    barrier.wait();
+                    let value: *const i32 = slot.get_or_init(|| Box::new(i));
+                    value as usize
+                })
+            })
+            .collect();
+
+        let addrs: Vec<_> = threads.into_iter().map(|t| t.join().unwrap()).collect();
+        assert!(addrs.iter().all(|&a| a == addrs[0]));
+        let winner = *slot.get().unwrap();
+        assert!((0..4).contains(&winner));
+    }
+}
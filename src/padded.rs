@@ -0,0 +1,117 @@
+//! A slot padded to a cache line.
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+
+use core::ops::{Deref, DerefMut};
+
+use crate::AtomicSlot;
+
+/// An [`AtomicSlot`] aligned and padded to its own cache line, so that neighbouring slots in an
+/// array do not contend on the same line.
+///
+/// Dereferences to the inner `AtomicSlot`, so every operation is available directly.
+///
+/// The cache line is assumed to be 64 bytes. Like `crossbeam-utils`, the padding is doubled to
+/// 128 bytes on x86-64, AArch64 and 64-bit PowerPC, where the hardware prefetches lines in
+/// pairs or the lines themselves are that large.
+///
+/// ```
+/// # use atomic_slot::PaddedAtomicSlot;
+/// let mailboxes: [PaddedAtomicSlot<u32>; 4] = Default::default();
+/// mailboxes[1].store(Some(Box::new(7)));
+/// assert_eq!(*mailboxes[1].take().unwrap(), 7);
+/// ```
+#[cfg_attr(
+    any(
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        target_arch = "powerpc64"
+    ),
+    repr(align(128))
+)]
+#[cfg_attr(
+    not(any(
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        target_arch = "powerpc64"
+    )),
+    repr(align(64))
+)]
+pub struct PaddedAtomicSlot<T> {
+    slot: AtomicSlot<T>,
+}
+
+impl<T> Default for PaddedAtomicSlot<T> {
+    /// Creates an empty `PaddedAtomicSlot<T>`.
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<T> core::fmt::Debug for PaddedAtomicSlot<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.slot.fmt(f)
+    }
+}
+
+impl<T> PaddedAtomicSlot<T> {
+    /// Creates a new `PaddedAtomicSlot` containing `value`.
+    pub fn new(value: Box<T>) -> Self {
+        AtomicSlot::new(value).into()
+    }
+
+    loom_const_fn! {
+        /// Creates an empty `PaddedAtomicSlot`.
+        pub const fn empty() -> Self {
+            Self {
+                slot: AtomicSlot::empty(),
+            }
+        }
+    }
+
+    /// Unwraps the inner `AtomicSlot`.
+    pub fn into_inner(self) -> AtomicSlot<T> {
+        self.slot
+    }
+}
+
+impl<T> From<AtomicSlot<T>> for PaddedAtomicSlot<T> {
+    fn from(slot: AtomicSlot<T>) -> Self {
+        Self { slot }
+    }
+}
+
+impl<T> Deref for PaddedAtomicSlot<T> {
+    type Target = AtomicSlot<T>;
+
+    fn deref(&self) -> &AtomicSlot<T> {
+        &self.slot
+    }
+}
+
+impl<T> DerefMut for PaddedAtomicSlot<T> {
+    fn deref_mut(&mut self) -> &mut AtomicSlot<T> {
+        &mut self.slot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn padded_to_a_cache_line() {
+        const { assert!(size_of::<PaddedAtomicSlot<u8>>() >= 64) };
+        const { assert!(align_of::<PaddedAtomicSlot<u8>>() >= 64) };
+
+        let slots = [
+            PaddedAtomicSlot::new(Box::new(1)),
+            PaddedAtomicSlot::empty(),
+        ];
+        let distance = (&slots[1] as *const _ as usize) - (&slots[0] as *const _ as usize);
+        assert!(distance >= 64);
+        assert_eq!(slots[1].swap(slots[0].take()).map(|b| *b), None);
+        assert_eq!(*slots[1].take().unwrap(), 1);
+    }
+}
@@ -0,0 +1,556 @@
+//! Operations spanning more than one [`AtomicSlot`].
+//!
+//! Each individual slot operation is atomic, but the combined operation is not: other threads
+//! may observe the intermediate states.
+
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, vec::Vec};
+
+use core::iter::Chain;
+use core::ops::{Deref, DerefMut};
+use core::option;
+
+use crate::AtomicSlot;
+use crate::sync::Ordering;
+
+/// The result of [`move_if_empty`].
+#[derive(Debug)]
+pub enum MoveOutcome<T> {
+    /// The value was moved from the source into the destination.
+    Moved,
+    /// The source was empty, nothing was moved.
+    SourceEmpty,
+    /// The destination was occupied, the value was put back into the source.
+    DestOccupied,
+    /// The destination was occupied and the source was refilled by another thread before the
+    /// value could be put back, so the value is handed back to the caller.
+    Returned(Box<T>),
+}
+
+/// Moves the value in `src` into `dst`, but only if `dst` is empty.
+///
+/// Takes from `src`, then attempts to store into `dst` if it is empty. If `dst` is occupied the
+/// value is put back into `src`, again only if `src` is still empty. A value is never dropped:
+/// if both slots were filled concurrently while the value was in flight, it is returned as
+/// [`MoveOutcome::Returned`].
+///
+/// ```
+/// # use atomic_slot::{AtomicSlot, MoveOutcome, move_if_empty};
+/// let src = AtomicSlot::new(Box::new(1));
+/// let dst = AtomicSlot::empty();
+/// assert!(matches!(move_if_empty(&src, &dst), MoveOutcome::Moved));
+/// assert_eq!(*dst.take().unwrap(), 1);
+/// ```
+pub fn move_if_empty<T>(src: &AtomicSlot<T>, dst: &AtomicSlot<T>) -> MoveOutcome<T> {
+    let Some(value) = src.take() else {
+        return MoveOutcome::SourceEmpty;
+    };
+    match dst.try_store(value) {
+        Ok(()) => MoveOutcome::Moved,
+        Err(value) => match src.try_store(value) {
+            Ok(()) => MoveOutcome::DestOccupied,
+            Err(value) => MoveOutcome::Returned(value),
+        },
+    }
+}
+
+/// Takes the value of the first occupied slot in `slots`, returning its index with the value.
+///
+/// Slots are listed from highest to lowest priority and tried from left to right, so a value
+/// is only taken from a slot when every slot before it was seen empty. Each slot is taken
+/// independently, so this is not an atomic scan: a higher-priority slot filled just after it
+/// was tried is left for the next call.
+///
+/// ```
+/// # use atomic_slot::{AtomicSlot, take_prioritized};
+/// let urgent = AtomicSlot::empty();
+/// let normal = AtomicSlot::new(Box::new("report"));
+/// assert_eq!(take_prioritized(&[&urgent, &normal]).map(|(i, v)| (i, *v)), Some((1, "report")));
+/// assert!(take_prioritized(&[&urgent, &normal]).is_none());
+/// ```
+pub fn take_prioritized<T>(slots: &[&AtomicSlot<T>]) -> Option<(usize, Box<T>)> {
+    slots
+        .iter()
+        .enumerate()
+        .find_map(|(i, slot)| slot.take().map(|value| (i, value)))
+}
+
+/// Takes values from `slots` into `buf` until `max` values are collected or every slot has
+/// been visited, returning how many were collected.
+///
+/// Slots are scanned from left to right and each is taken independently, so the batch is not
+/// an atomic snapshot of the pool.
+///
+/// ```
+/// # use atomic_slot::{AtomicSlot, take_batch_into};
+/// let pool = [AtomicSlot::new(Box::new(1)), AtomicSlot::empty(), AtomicSlot::new(Box::new(2))];
+/// let mut buf = Vec::new();
+/// assert_eq!(take_batch_into(&pool, &mut buf, 8), 2);
+/// assert_eq!(buf.iter().map(|b| **b).collect::<Vec<_>>(), [1, 2]);
+/// ```
+pub fn take_batch_into<T>(slots: &[AtomicSlot<T>], buf: &mut Vec<Box<T>>, max: usize) -> usize {
+    let mut taken = 0;
+    for slot in slots {
+        if taken == max {
+            break;
+        }
+        if let Some(value) = slot.take() {
+            buf.push(value);
+            taken += 1;
+        }
+    }
+    taken
+}
+
+/// Takes every value out of `slots`, in slot order, leaving them all empty.
+///
+/// Each slot is taken independently, so this is not an atomic snapshot: a slot refilled
+/// after it was visited keeps its new value.
+///
+/// ```
+/// # use atomic_slot::{AtomicSlot, take_all};
+/// let mailboxes = [AtomicSlot::new(Box::new(1)), AtomicSlot::empty(), AtomicSlot::new(Box::new(2))];
+/// let jobs: Vec<_> = take_all(&mailboxes).into_iter().map(|b| *b).collect();
+/// assert_eq!(jobs, [1, 2]);
+/// ```
+pub fn take_all<T>(slots: &[AtomicSlot<T>]) -> Vec<Box<T>> {
+    let mut out = Vec::new();
+    take_all_into(slots, &mut out);
+    out
+}
+
+/// Takes every value out of `slots` into `out`, in slot order, leaving them all empty.
+///
+/// Like [`take_all`], but appends to any collection, such as a fixed-capacity buffer.
+pub fn take_all_into<T>(slots: &[AtomicSlot<T>], out: &mut impl Extend<Box<T>>) {
+    out.extend(slots.iter().filter_map(AtomicSlot::take));
+}
+
+/// Places `values` into the empty slots of `slots`, returning the values that did not fit.
+///
+/// Slots are filled from left to right; occupied slots are skipped and never overwritten.
+/// Each slot is filled independently, so other threads may see the pool partially filled,
+/// and a slot that is taken concurrently may or may not be refilled.
+///
+/// The returned iterator yields the value that found no empty slot, if any, followed by the
+/// unconsumed rest of `values`.
+///
+/// ```
+/// # use atomic_slot::{AtomicSlot, fill_empty};
+/// let pool = [AtomicSlot::empty(), AtomicSlot::new(Box::new(0)), AtomicSlot::empty()];
+/// let leftover: Vec<_> = fill_empty(&pool, (1..=4).map(Box::new)).collect();
+/// assert_eq!(leftover.iter().map(|b| **b).collect::<Vec<_>>(), [3, 4]);
+/// assert_eq!(*pool[2].take().unwrap(), 2);
+/// ```
+pub fn fill_empty<T, I>(
+    slots: &[AtomicSlot<T>],
+    mut values: I,
+) -> Chain<option::IntoIter<Box<T>>, I>
+where
+    I: Iterator<Item = Box<T>>,
+{
+    let mut pending = None;
+    for slot in slots {
+        let Some(value) = pending.take().or_else(|| values.next()) else {
+            break;
+        };
+        if let Err(value) = slot.try_store(value) {
+            pending = Some(value);
+        }
+    }
+    pending.into_iter().chain(values)
+}
+
+/// Stores successive `items` into the empty slots of `slots`, returning how many were
+/// stored along with the item that was pulled but could not be stored, if any.
+///
+/// Slots are filled from left to right and occupied slots are skipped. An item is only
+/// pulled from `items` once an empty slot has been found, so passing `&mut iter` leaves the
+/// items that did not fit in `iter`. The one exception is an item whose slot is filled by
+/// another thread first and which then finds no later empty slot: it is returned instead.
+/// [`fill_empty`] hands back every leftover as a single iterator.
+///
+/// ```
+/// # use atomic_slot::{AtomicSlot, fill_from_iter};
+/// let mailboxes: [AtomicSlot<u32>; 3] = Default::default();
+/// let mut items = (1..=5).map(Box::new);
+/// let (stored, pending) = fill_from_iter(&mailboxes, &mut items);
+/// assert_eq!(stored, 3);
+/// assert!(pending.is_none());
+/// assert_eq!(*items.next().unwrap(), 4);
+/// ```
+pub fn fill_from_iter<T>(
+    slots: &[AtomicSlot<T>],
+    items: impl IntoIterator<Item = Box<T>>,
+) -> (usize, Option<Box<T>>) {
+    let mut items = items.into_iter();
+    let mut pending = None;
+    let mut stored = 0;
+    for slot in slots {
+        if slot.is_some() {
+            continue;
+        }
+        let Some(value) = pending.take().or_else(|| items.next()) else {
+            break;
+        };
+        match slot.try_store(value) {
+            Ok(()) => stored += 1,
+            Err(value) => pending = Some(value),
+        }
+    }
+    (stored, pending)
+}
+
+/// Returns how many of `slots` are occupied, loading each one with `order`.
+///
+/// The slots are loaded one after another, so this is a racy snapshot: under concurrent
+/// swaps the count may not match the pool at any single point in time. `Relaxed` suffices
+/// for metrics.
+///
+/// ```
+/// # use atomic_slot::{AtomicSlot, occupied_count};
+/// # use std::sync::atomic::Ordering;
+/// let slots = [AtomicSlot::new(Box::new(1)), AtomicSlot::empty(), AtomicSlot::new(Box::new(2))];
+/// assert_eq!(occupied_count(&slots, Ordering::Relaxed), 2);
+/// ```
+pub fn occupied_count<T>(slots: &[AtomicSlot<T>], order: Ordering) -> usize {
+    slots
+        .iter()
+        .filter(|slot| slot.is_some_ordered(order))
+        .count()
+}
+
+/// Takes every value out of `slots` as a rayon parallel iterator.
+///
+/// Each slot is taken atomically and independently on whichever worker thread reaches it;
+/// empty slots are skipped. Like [`take_batch_into`], the drain is not an atomic snapshot of
+/// the pool, so a slot refilled after it was visited keeps its new value.
+///
+/// ```
+/// # use atomic_slot::{AtomicSlot, par_drain};
+/// use rayon::prelude::*;
+///
+/// let pool: Vec<_> = (0..4).map(|i| AtomicSlot::new(Box::new(i))).collect();
+/// let sum: i32 = par_drain(&pool).map(|b| *b).sum();
+/// assert_eq!(sum, 6);
+/// ```
+#[cfg(feature = "rayon")]
+pub fn par_drain<T: Send>(
+    slots: &[AtomicSlot<T>],
+) -> impl rayon::iter::ParallelIterator<Item = Box<T>> + '_ {
+    use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+
+    slots.par_iter().filter_map(AtomicSlot::take)
+}
+
+/// Drops every value in `slots` for which `keep` returns `false`, visiting the slots in
+/// parallel on the rayon thread pool.
+///
+/// Each occupied slot is taken, tested and then either dropped or put back only if the slot is
+/// still empty. If another thread fills a slot while its value is being tested, the newer
+/// value wins and the tested one is dropped, even if `keep` accepted it. Other threads see the
+/// slot as empty while its value is tested.
+///
+/// ```
+/// # use atomic_slot::{AtomicSlot, par_retain};
+/// let pool: Vec<_> = (0..4).map(|i| AtomicSlot::new(Box::new(i))).collect();
+/// par_retain(&pool, |v| v % 2 == 0);
+/// assert!(pool[0].is_some() && pool[1].is_none());
+/// ```
+#[cfg(feature = "rayon")]
+pub fn par_retain<T, F>(slots: &[AtomicSlot<T>], keep: F)
+where
+    T: Send + Sync,
+    F: Fn(&T) -> bool + Sync,
+{
+    use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+
+    slots.par_iter().for_each(|slot| {
+        if let Some(value) = slot.take()
+            && keep(&value)
+        {
+            slot.restore(value);
+        }
+    });
+}
+
+/// A fixed-capacity pool of reusable objects, each kept in its own [`AtomicSlot`].
+///
+/// [`checkout`](Pool::checkout) takes an idle object out of the first occupied slot and
+/// wraps it in a [`Pooled`] guard, which puts it back into the first empty slot when dropped.
+/// Objects are moved as boxes, so checking one out or in never allocates.
+///
+/// Slots are scanned independently, so under contention a checkout can miss an object that
+/// is being returned at the same time. If every slot is occupied when an object comes back,
+/// for instance because [`put`](Pool::put) added more objects meanwhile, it is dropped.
+///
+/// ```
+/// # use atomic_slot::Pool;
+/// let pool = Pool::with_capacity(2);
+/// pool.put(Box::new(Vec::<u8>::with_capacity(4096))).unwrap();
+///
+/// let mut buf = pool.checkout().unwrap();
+/// buf.extend_from_slice(b"hello");
+/// assert!(pool.checkout().is_none());
+/// drop(buf);
+/// assert_eq!(&**pool.checkout().unwrap(), b"hello");
+/// ```
+pub struct Pool<T> {
+    slots: Box<[AtomicSlot<T>]>,
+}
+
+impl<T> Pool<T> {
+    /// Creates a pool with room for `capacity` idle objects, initially empty.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            slots: (0..capacity).map(|_| AtomicSlot::empty()).collect(),
+        }
+    }
+
+    /// Adds an idle object to the pool, handing it back if every slot is occupied.
+    pub fn put(&self, value: Box<T>) -> Result<(), Box<T>> {
+        fill_empty(&self.slots, core::iter::once(value))
+            .next()
+            .map_or(Ok(()), Err)
+    }
+
+    /// Checks out an idle object, returning `None` if none is available.
+    pub fn checkout(&self) -> Option<Pooled<'_, T>> {
+        let value = self.slots.iter().find_map(AtomicSlot::take)?;
+        Some(Pooled {
+            pool: self,
+            value: Some(value),
+        })
+    }
+
+    /// Returns the number of idle objects the pool can hold.
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+}
+
+impl<T> FromIterator<Box<T>> for Pool<T> {
+    /// Creates a pool holding every item, with exactly one slot per item.
+    ///
+    /// ```
+    /// # use atomic_slot::Pool;
+    /// let pool: Pool<String> = ["a", "b"].map(|s| Box::new(s.into())).into_iter().collect();
+    /// assert_eq!(pool.capacity(), 2);
+    /// assert_eq!(&**pool.checkout().unwrap(), "a");
+    /// ```
+    fn from_iter<I: IntoIterator<Item = Box<T>>>(iter: I) -> Self {
+        Self {
+            slots: iter.into_iter().map(AtomicSlot::new).collect(),
+        }
+    }
+}
+
+/// An object checked out of a [`Pool`], returned to it when dropped.
+pub struct Pooled<'a, T> {
+    pool: &'a Pool<T>,
+    /// Always `Some` until dropped.
+    value: Option<Box<T>>,
+}
+
+impl<T> Pooled<'_, T> {
+    /// Detaches the object from the pool, so it is not returned on drop.
+    pub fn into_inner(mut self) -> Box<T> {
+        self.value.take().expect("value present until drop")
+    }
+}
+
+impl<T> Deref for Pooled<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value.as_ref().expect("value present until drop")
+    }
+}
+
+impl<T> DerefMut for Pooled<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value.as_mut().expect("value present until drop")
+    }
+}
+
+impl<T> Drop for Pooled<'_, T> {
+    fn drop(&mut self) {
+        if let Some(value) = self.value.take() {
+            let _ = self.pool.put(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn take_prioritized_picks_highest_occupied() {
+        let slots: Vec<_> = (0..4).map(|_| AtomicSlot::empty()).collect();
+        slots[1].store(Some(Box::new(1)));
+        slots[3].store(Some(Box::new(3)));
+        let by_priority: Vec<_> = slots.iter().collect();
+
+        let (index, value) = take_prioritized(&by_priority).unwrap();
+        assert_eq!((index, *value), (1, 1));
+        slots[0].store(Some(Box::new(0)));
+        assert_eq!(take_prioritized(&by_priority).map(|(i, _)| i), Some(0));
+        assert_eq!(take_prioritized(&by_priority).map(|(i, _)| i), Some(3));
+        assert!(take_prioritized(&by_priority).is_none());
+        assert!(take_prioritized::<u8>(&[]).is_none());
+    }
+
+    #[test]
+    fn take_batch_into_is_bounded() {
+        let pool: Vec<_> = (0..10).map(|i| AtomicSlot::new(Box::new(i))).collect();
+        pool[2].store(None);
+
+        let mut buf = Vec::new();
+        assert_eq!(take_batch_into(&pool, &mut buf, 4), 4);
+        assert_eq!(buf.iter().map(|b| **b).collect::<Vec<_>>(), [0, 1, 3, 4]);
+
+        assert_eq!(take_batch_into(&pool, &mut buf, 100), 5);
+        assert_eq!(buf.len(), 9);
+        assert!(pool.iter().all(AtomicSlot::is_none));
+        assert_eq!(take_batch_into(&pool, &mut buf, 100), 0);
+    }
+
+    #[test]
+    fn take_all_returns_populated_slots() {
+        let slots: [AtomicSlot<usize>; 8] = core::array::from_fn(|i| {
+            if i % 3 == 0 {
+                AtomicSlot::new(Box::new(i))
+            } else {
+                AtomicSlot::empty()
+            }
+        });
+        let taken: Vec<_> = take_all(&slots).into_iter().map(|b| *b).collect();
+        assert_eq!(taken, [0, 3, 6]);
+        assert!(slots.iter().all(AtomicSlot::is_none));
+
+        slots[5].store(Some(Box::new(5)));
+        let mut out = vec![Box::new(100)];
+        take_all_into(&slots, &mut out);
+        assert_eq!(out.iter().map(|b| **b).collect::<Vec<_>>(), [100, 5]);
+    }
+
+    #[test]
+    fn fill_empty_returns_leftovers() {
+        let pool: Vec<_> = (0..5).map(|_| AtomicSlot::empty()).collect();
+        pool[1].store(Some(Box::new(100)));
+        pool[3].store(Some(Box::new(300)));
+
+        let leftover: Vec<_> = fill_empty(&pool, (0..6).map(Box::new))
+            .map(|b| *b)
+            .collect();
+        assert_eq!(leftover, [3, 4, 5]);
+
+        let contents: Vec<_> = pool.iter().map(|s| *s.take().unwrap()).collect();
+        assert_eq!(contents, [0, 100, 1, 300, 2]);
+    }
+
+    #[test]
+    fn fill_from_iter_with_more_items_than_slots() {
+        let slots: Vec<_> = (0..4).map(|_| AtomicSlot::empty()).collect();
+        slots[1].store(Some(Box::new(100)));
+
+        let mut items = (0..6).map(Box::new);
+        assert_eq!(fill_from_iter(&slots, &mut items).0, 3);
+        assert_eq!(items.map(|b| *b).collect::<Vec<_>>(), [3, 4, 5]);
+
+        let contents: Vec<_> = slots.iter().map(|s| *s.take().unwrap()).collect();
+        assert_eq!(contents, [0, 100, 1, 2]);
+    }
+
+    #[test]
+    fn fill_from_iter_with_fewer_items_than_slots() {
+        let slots: Vec<_> = (0..4).map(|_| AtomicSlot::empty()).collect();
+        assert_eq!(fill_from_iter(&slots, (0..2).map(Box::new)).0, 2);
+        assert_eq!(*slots[1].take().unwrap(), 1);
+        assert!(slots[2].is_none() && slots[3].is_none());
+
+        assert!(matches!(
+            fill_from_iter(&slots, core::iter::empty()),
+            (0, None)
+        ));
+        let pool: Pool<i32> = core::iter::empty().collect();
+        assert_eq!(pool.capacity(), 0);
+    }
+
+    #[test]
+    fn occupied_count_over_mixed_slots() {
+        let slots: Vec<_> = (0..7)
+            .map(|i| {
+                if i % 2 == 0 {
+                    AtomicSlot::new(Box::new(i))
+                } else {
+                    AtomicSlot::empty()
+                }
+            })
+            .collect();
+        assert_eq!(occupied_count(&slots, Ordering::Acquire), 4);
+        drop(slots[0].take());
+        assert_eq!(occupied_count(&slots, Ordering::Relaxed), 3);
+        assert_eq!(occupied_count::<i32>(&[], Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn pooled_objects_are_reused() {
+        use std::sync::Arc;
+
+        let tracker = Arc::new(());
+        let pool = Pool::with_capacity(2);
+        pool.put(Box::new(tracker.clone())).unwrap();
+        pool.put(Box::new(tracker.clone())).unwrap();
+        assert!(pool.put(Box::new(tracker.clone())).is_err());
+        assert_eq!(Arc::strong_count(&tracker), 3);
+
+        let a = pool.checkout().unwrap();
+        let b = pool.checkout().unwrap();
+        assert!(pool.checkout().is_none());
+        let addr = &*a as *const Arc<()>;
+        drop(a);
+        let again = pool.checkout().unwrap();
+        assert_eq!(&*again as *const Arc<()>, addr);
+
+        drop(b.into_inner());
+        drop(again);
+        assert_eq!(Arc::strong_count(&tracker), 2);
+        drop(pool);
+        assert_eq!(Arc::strong_count(&tracker), 1);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_drain_collects_every_value() {
+        use rayon::iter::ParallelIterator;
+
+        let pool: Vec<_> = (0..1000).map(|i| AtomicSlot::new(Box::new(i))).collect();
+        for slot in pool.iter().step_by(3) {
+            slot.store(None);
+        }
+
+        let mut drained: Vec<_> = par_drain(&pool).map(|b| *b).collect();
+        drained.sort_unstable();
+        let expected: Vec<_> = (0..1000).filter(|i| i % 3 != 0).collect();
+        assert_eq!(drained, expected);
+        assert!(pool.iter().all(AtomicSlot::is_none));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_retain_evicts_rejected_values() {
+        let pool: Vec<_> = (0..1000).map(|i| AtomicSlot::new(Box::new(i))).collect();
+        pool[1].store(None);
+
+        par_retain(&pool, |&v| v % 5 == 1);
+        let survivors: Vec<_> = pool
+            .iter()
+            .filter_map(AtomicSlot::take)
+            .map(|b| *b)
+            .collect();
+        let expected: Vec<_> = (2..1000).filter(|i| i % 5 == 1).collect();
+        assert_eq!(survivors, expected);
+    }
+}
@@ -0,0 +1,108 @@
+//! A slot paired with an epoch that counts publishes.
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+
+use crate::AtomicSlot;
+use crate::sync::atomic::{AtomicU64, Ordering};
+
+/// A slot for read-mostly values whose readers poll an epoch to know when to take them again.
+///
+/// Every [`publish`](PublishSlot::publish) swaps in a new value and then bumps the epoch, so
+/// readers can check [`current_epoch`](PublishSlot::current_epoch), a single atomic load,
+/// instead of touching the value on every poll. Unlike
+/// [`VersionedSlot`](crate::VersionedSlot), the value and its number are not published together:
+/// the slot is a plain [`AtomicSlot`] that hands the value out with
+/// [`take`](PublishSlot::take), and the epoch only says how many publishes have completed.
+///
+/// # Ordering
+///
+/// The value is swapped in with acquire-release ordering, and only afterwards is the epoch
+/// incremented with release ordering. `current_epoch` loads it with acquire ordering, so a
+/// reader that sees epoch `n` also sees the value of the `n`th publish, or a later one, when it
+/// then takes from the slot, unless another reader took it first. While a publish is in
+/// progress the new value may already be in the slot with the epoch still trailing.
+pub struct PublishSlot<T> {
+    slot: AtomicSlot<T>,
+    epoch: AtomicU64,
+}
+
+impl<T> Default for PublishSlot<T> {
+    /// Creates an empty `PublishSlot<T>` at epoch 0.
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<T> PublishSlot<T> {
+    /// Creates an empty `PublishSlot` at epoch 0.
+    pub fn empty() -> Self {
+        Self {
+            slot: AtomicSlot::empty(),
+            epoch: AtomicU64::new(0),
+        }
+    }
+
+    /// Swaps in `value` and bumps the epoch, returning the value it replaced.
+    ///
+    /// ```
+    /// # use atomic_slot::PublishSlot;
+    /// let config = PublishSlot::empty();
+    /// assert!(config.publish(Box::new("a")).is_none());
+    /// assert_eq!(*config.publish(Box::new("b")).unwrap(), "a");
+    /// assert_eq!(config.current_epoch(), 2);
+    /// ```
+    pub fn publish(&self, value: Box<T>) -> Option<Box<T>> {
+        let prev = self.slot.swap_ordered(Some(value), Ordering::AcqRel);
+        // Bumped only once the value is installed, so an epoch implies its value
+        self.epoch.fetch_add(1, Ordering::Release);
+        prev
+    }
+
+    /// Returns the number of completed publishes, without touching the value.
+    ///
+    /// ```
+    /// # use atomic_slot::PublishSlot;
+    /// let config = PublishSlot::empty();
+    /// let seen = config.current_epoch();
+    /// config.publish(Box::new(8080));
+    /// if config.current_epoch() != seen {
+    ///     assert_eq!(*config.take().unwrap(), 8080);
+    /// }
+    /// ```
+    pub fn current_epoch(&self) -> u64 {
+        self.epoch.load(Ordering::Acquire)
+    }
+
+    /// Takes the current value, leaving the slot empty.
+    ///
+    /// Taking does not change the epoch.
+    pub fn take(&self) -> Option<Box<T>> {
+        self.slot.take()
+    }
+
+    /// Returns `true` if the slot currently contains a value.
+    pub fn is_some(&self) -> bool {
+        self.slot.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epoch_counts_publishes() {
+        let slot = PublishSlot::empty();
+        assert_eq!(slot.current_epoch(), 0);
+        assert!(!slot.is_some());
+
+        for i in 1..=3 {
+            slot.publish(Box::new(i));
+            assert_eq!(slot.current_epoch(), i);
+        }
+        assert_eq!(*slot.take().unwrap(), 3);
+        assert!(slot.take().is_none());
+        assert_eq!(slot.current_epoch(), 3);
+    }
+}
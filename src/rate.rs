@@ -0,0 +1,178 @@
+//! Gating slot consumption with a token bucket.
+
+use std::time::{Duration, Instant};
+
+use crate::AtomicSlot;
+use crate::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+/// A lock-free token bucket that refills one token per interval, up to its capacity.
+///
+/// Tokens are added lazily when one is requested, so an idle bucket costs nothing.
+pub struct TokenBucket {
+    capacity: u32,
+    tokens: AtomicU32,
+    interval: Duration,
+    start: Instant,
+    /// Nanoseconds since `start` up to which refills have been credited.
+    refilled: AtomicU64,
+}
+
+impl TokenBucket {
+    /// Creates a full bucket holding `capacity` tokens that gains one token every `interval`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interval` is zero.
+    pub fn new(capacity: u32, interval: Duration) -> Self {
+        assert!(!interval.is_zero(), "interval must be non-zero");
+        Self {
+            capacity,
+            tokens: AtomicU32::new(capacity),
+            interval,
+            start: Instant::now(),
+            refilled: AtomicU64::new(0),
+        }
+    }
+
+    /// Takes a token if one is available, returning whether it did.
+    ///
+    /// ```
+    /// # use atomic_slot::TokenBucket;
+    /// use std::time::Duration;
+    ///
+    /// let bucket = TokenBucket::new(1, Duration::from_secs(60));
+    /// assert!(bucket.try_acquire());
+    /// assert!(!bucket.try_acquire());
+    /// ```
+    pub fn try_acquire(&self) -> bool {
+        self.refill();
+        self.tokens
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| n.checked_sub(1))
+            .is_ok()
+    }
+
+    /// Returns the number of tokens currently available.
+    pub fn available(&self) -> u32 {
+        self.refill();
+        self.tokens.load(Ordering::Acquire)
+    }
+
+    /// Gives back a token that was acquired but not used.
+    fn release(&self) {
+        self.add(1);
+    }
+
+    /// Credits the intervals elapsed since the last refill.
+    fn refill(&self) {
+        let interval = self.interval.as_nanos() as u64;
+        let now = self.start.elapsed().as_nanos() as u64;
+        let mut refilled = self.refilled.load(Ordering::Acquire);
+        loop {
+            let elapsed = (now.saturating_sub(refilled)) / interval;
+            if elapsed == 0 {
+                return;
+            }
+            match self.refilled.compare_exchange_weak(
+                refilled,
+                refilled + elapsed * interval,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => break self.add(u32::try_from(elapsed).unwrap_or(u32::MAX)),
+                Err(actual) => refilled = actual,
+            }
+        }
+    }
+
+    fn add(&self, tokens: u32) {
+        let _ = self
+            .tokens
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+                Some(n.saturating_add(tokens).min(self.capacity))
+            });
+    }
+}
+
+/// The result of [`AtomicSlot::take_rate_limited`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum RateLimited<T> {
+    /// A token was available and the value was taken.
+    Ready(T),
+    /// No token was available; the value, if any, was left in the slot.
+    Throttled,
+    /// The slot was empty, so no token was used.
+    Empty,
+}
+
+impl<T> AtomicSlot<T> {
+    /// Takes the current contents if `bucket` has a token to spend on them.
+    ///
+    /// On [`Throttled`](RateLimited::Throttled) the value remains in the slot for a later
+    /// attempt. A token is only consumed when a value is actually taken: if the slot is
+    /// emptied by another thread between acquiring the token and taking, the token is given
+    /// back and [`Empty`](RateLimited::Empty) is returned.
+    ///
+    /// ```
+    /// # use atomic_slot::{AtomicSlot, RateLimited, TokenBucket};
+    /// use std::time::Duration;
+    ///
+    /// let bucket = TokenBucket::new(1, Duration::from_secs(60));
+    /// let slot = AtomicSlot::new(Box::new(1));
+    /// assert_eq!(slot.take_rate_limited(&bucket), RateLimited::Ready(Box::new(1)));
+    /// slot.store(Some(Box::new(2)));
+    /// assert_eq!(slot.take_rate_limited(&bucket), RateLimited::Throttled);
+    /// assert!(slot.is_some());
+    /// ```
+    pub fn take_rate_limited(&self, bucket: &TokenBucket) -> RateLimited<Box<T>> {
+        if self.is_none() {
+            return RateLimited::Empty;
+        }
+        if !bucket.try_acquire() {
+            return RateLimited::Throttled;
+        }
+        match self.take() {
+            Some(value) => RateLimited::Ready(value),
+            None => {
+                bucket.release();
+                RateLimited::Empty
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn throttled_value_remains() {
+        let bucket = TokenBucket::new(0, Duration::from_secs(60));
+        let slot = AtomicSlot::new(Box::new("job"));
+        assert_eq!(slot.take_rate_limited(&bucket), RateLimited::Throttled);
+        assert_eq!(*slot.take().unwrap(), "job");
+        assert_eq!(slot.take_rate_limited(&bucket), RateLimited::Empty);
+    }
+
+    #[test]
+    fn token_is_spent_on_take_and_refilled() {
+        let bucket = TokenBucket::new(1, Duration::from_millis(5));
+        let slot = AtomicSlot::empty();
+        assert_eq!(slot.take_rate_limited(&bucket), RateLimited::Empty);
+        assert_eq!(bucket.available(), 1);
+
+        slot.store(Some(Box::new(1)));
+        assert_eq!(
+            slot.take_rate_limited(&bucket),
+            RateLimited::Ready(Box::new(1))
+        );
+        slot.store(Some(Box::new(2)));
+        while bucket.available() == 0 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(
+            slot.take_rate_limited(&bucket),
+            RateLimited::Ready(Box::new(2))
+        );
+    }
+}
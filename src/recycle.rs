@@ -0,0 +1,91 @@
+//! Reuse of box allocations between slot operations.
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+
+use core::mem::MaybeUninit;
+use core::ptr;
+
+use crate::AtomicSlot;
+
+/// A bounded, lock-free store of spare `Box<T>` allocations.
+///
+/// Boxes handed to [`recycle`](Recycler::recycle) have their value dropped and their
+/// allocation kept, and [`alloc`](Recycler::alloc) reuses a spare allocation before falling
+/// back to the global allocator. Once warm, a hot loop of [`AtomicSlot::swap_recycled`] and
+/// `recycle` performs no allocations.
+///
+/// The spares are held in a fixed array of [`AtomicSlot`]s, so a `Recycler` can be shared
+/// between threads by reference: any thread may allocate from and recycle into it
+/// concurrently. Boxes recycled while every spare cell is full are freed.
+pub struct Recycler<T> {
+    spares: Box<[AtomicSlot<MaybeUninit<T>>]>,
+}
+
+impl<T> Recycler<T> {
+    /// Creates a recycler that keeps at most `capacity` spare allocations.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            spares: (0..capacity).map(|_| AtomicSlot::empty()).collect(),
+        }
+    }
+
+    /// Moves `value` into a box, reusing a spare allocation if one is available.
+    ///
+    /// ```
+    /// # use atomic_slot::Recycler;
+    /// let recycler = Recycler::with_capacity(1);
+    /// let first = recycler.alloc(1u64);
+    /// let addr = &*first as *const u64;
+    /// recycler.recycle(first);
+    /// let second = recycler.alloc(2u64);
+    /// assert_eq!(&*second as *const u64, addr);
+    /// ```
+    pub fn alloc(&self, value: T) -> Box<T> {
+        match self.spares.iter().find_map(AtomicSlot::take) {
+            Some(spare) => Box::write(spare, value),
+            None => Box::new(value),
+        }
+    }
+
+    /// Drops the value in `boxed` and keeps its allocation for reuse.
+    pub fn recycle(&self, boxed: Box<T>) {
+        let raw = Box::into_raw(boxed);
+        // Safety: `raw` is a live, uniquely owned value from `Box::into_raw`, and after
+        // dropping it the allocation is valid storage for an uninitialized `T`
+        let mut spare = unsafe {
+            ptr::drop_in_place(raw);
+            Box::from_raw(raw.cast::<MaybeUninit<T>>())
+        };
+        for slot in self.spares.iter() {
+            match slot.try_store(spare) {
+                Ok(()) => return,
+                Err(rejected) => spare = rejected,
+            }
+        }
+    }
+}
+
+impl<T> AtomicSlot<T> {
+    /// Swaps `value` into the slot, reusing a spare allocation from `recycler`.
+    ///
+    /// Returns the old contents. Handing consumed boxes back to the recycler with
+    /// [`Recycler::recycle`] keeps the cycle free of allocator traffic.
+    ///
+    /// Uses acquire–release ordering.
+    ///
+    /// ```
+    /// # use atomic_slot::{AtomicSlot, Recycler};
+    /// let recycler = Recycler::with_capacity(2);
+    /// let slot = AtomicSlot::empty();
+    /// for i in 0..4 {
+    ///     if let Some(old) = slot.swap_recycled(i, &recycler) {
+    ///         recycler.recycle(old);
+    ///     }
+    /// }
+    /// assert_eq!(*slot.take().unwrap(), 3);
+    /// ```
+    pub fn swap_recycled(&self, value: T, recycler: &Recycler<T>) -> Option<Box<T>> {
+        self.swap(Some(recycler.alloc(value)))
+    }
+}
@@ -0,0 +1,178 @@
+//! A slot whose right to be filled can be claimed before the value exists.
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+
+use core::marker::PhantomData;
+use core::mem;
+use core::ptr;
+
+use crate::sync::atomic::{AtomicPtr, Ordering};
+use crate::sync::with_ptr_mut;
+
+/// Its address marks a reserved slot; no `Box` can ever share it.
+static RESERVED: u8 = 0;
+
+fn reserved<T>() -> *mut T {
+    ptr::addr_of!(RESERVED).cast_mut().cast()
+}
+
+/// A slot like [`AtomicSlot`](crate::AtomicSlot) that a producer can reserve before its value
+/// is ready.
+///
+/// Reserving installs a sentinel in the empty slot, so when several producers race only one of
+/// them goes on to prepare a value and the rest can skip the work. The slot stays a single
+/// word.
+pub struct ReserveSlot<T> {
+    inner: AtomicPtr<T>,
+    _phantom: PhantomData<Option<Box<T>>>,
+}
+
+// Safety: the word is null, the address of the `RESERVED` static, or an owned `Box<T>`;
+// `Reservation::fulfill` moves a box in and `take` moves it out, and the sentinel is never
+// dereferenced, so through `&self` a `T` is only ever moved between threads
+unsafe impl<T: Send> Send for ReserveSlot<T> {}
+unsafe impl<T: Send> Sync for ReserveSlot<T> {}
+
+impl<T> Default for ReserveSlot<T> {
+    /// Creates an empty `ReserveSlot<T>`.
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<T> ReserveSlot<T> {
+    /// Creates a new `ReserveSlot` containing `value`.
+    pub fn new(value: Box<T>) -> Self {
+        Self {
+            inner: AtomicPtr::new(Box::into_raw(value)),
+            _phantom: PhantomData,
+        }
+    }
+
+    loom_const_fn! {
+        /// Creates an empty `ReserveSlot`.
+        pub const fn empty() -> Self {
+            Self {
+                inner: AtomicPtr::new(ptr::null_mut()),
+                _phantom: PhantomData,
+            }
+        }
+    }
+
+    /// Reserves the slot if it is empty, returning `None` if it is full or already reserved.
+    ///
+    /// The slot stays reserved until the [`Reservation`] is fulfilled or dropped. Uses
+    /// acquire ordering.
+    ///
+    /// ```
+    /// # use atomic_slot::ReserveSlot;
+    /// let slot = ReserveSlot::empty();
+    /// let reservation = slot.reserve().unwrap();
+    /// assert!(slot.reserve().is_none());
+    /// reservation.fulfill(Box::new(3));
+    /// assert_eq!(*slot.take().unwrap(), 3);
+    /// ```
+    pub fn reserve(&self) -> Option<Reservation<'_, T>> {
+        self.inner
+            .compare_exchange(
+                ptr::null_mut(),
+                reserved(),
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            )
+            .ok()
+            .map(|_| Reservation { slot: self })
+    }
+
+    /// Takes the current contents, leaving the slot empty.
+    ///
+    /// Returns `None` if the slot is empty or only reserved; a reservation is never taken
+    /// away from its holder. Uses acquire–release ordering.
+    pub fn take(&self) -> Option<Box<T>> {
+        let mut current = self.inner.load(Ordering::Acquire);
+        while !current.is_null() && current != reserved() {
+            match self.inner.compare_exchange_weak(
+                current,
+                ptr::null_mut(),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                // Safety: anything but null and the sentinel comes from `Box::into_raw`, and
+                // the exchange made us its only owner
+                Ok(_) => return Some(unsafe { Box::from_raw(current) }),
+                Err(actual) => current = actual,
+            }
+        }
+        None
+    }
+
+    /// Returns `true` if the slot currently contains a value. Uses acquire ordering.
+    pub fn is_some(&self) -> bool {
+        let current = self.inner.load(Ordering::Acquire);
+        !current.is_null() && current != reserved()
+    }
+
+    /// Returns `true` if the slot is reserved but not yet fulfilled. Uses acquire ordering.
+    pub fn is_reserved(&self) -> bool {
+        self.inner.load(Ordering::Acquire) == reserved()
+    }
+}
+
+impl<T> Drop for ReserveSlot<T> {
+    fn drop(&mut self) {
+        let current = with_ptr_mut(&mut self.inner, |ptr| *ptr);
+        // A live reservation borrows the slot, so the sentinel cannot be present here
+        if !current.is_null() {
+            // Safety: non-null pointers other than the sentinel come from `Box::into_raw`
+            drop(unsafe { Box::from_raw(current) });
+        }
+    }
+}
+
+/// The exclusive right to fill a [`ReserveSlot`], created by [`ReserveSlot::reserve`].
+///
+/// Dropping the reservation without fulfilling it makes the slot empty again.
+#[must_use = "dropping a reservation immediately releases it"]
+pub struct Reservation<'a, T> {
+    slot: &'a ReserveSlot<T>,
+}
+
+impl<T> Reservation<'_, T> {
+    /// Fills the reserved slot with `value`. Uses release ordering.
+    pub fn fulfill(self, value: Box<T>) {
+        // Only the holder of the reservation may replace the sentinel
+        self.slot
+            .inner
+            .store(Box::into_raw(value), Ordering::Release);
+        mem::forget(self);
+    }
+}
+
+impl<T> Drop for Reservation<'_, T> {
+    fn drop(&mut self) {
+        self.slot.inner.store(ptr::null_mut(), Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dropped_reservation_clears_slot() {
+        let slot = ReserveSlot::<u32>::empty();
+        let reservation = slot.reserve().unwrap();
+        assert!(slot.is_reserved());
+        assert!(!slot.is_some());
+        assert!(slot.take().is_none());
+        drop(reservation);
+
+        assert!(!slot.is_reserved());
+        assert!(slot.take().is_none());
+        slot.reserve().unwrap().fulfill(Box::new(9));
+        assert!(slot.is_some());
+        assert!(slot.reserve().is_none());
+        assert_eq!(*slot.take().unwrap(), 9);
+    }
+}
@@ -0,0 +1,256 @@
+//! A one-shot response slot whose requester can cancel before the response arrives.
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+
+use core::fmt;
+use core::future::Future;
+use core::marker::PhantomData;
+use core::pin::Pin;
+use core::ptr;
+use core::task::{Context, Poll};
+
+use atomic_waker::AtomicWaker;
+
+use crate::sync::atomic::{AtomicPtr, Ordering};
+use crate::sync::with_ptr_mut;
+
+/// Its address marks a closed slot; no `Box` can ever share it.
+static CLOSED: u8 = 0;
+
+fn closed<T>() -> *mut T {
+    ptr::addr_of!(CLOSED).cast_mut().cast()
+}
+
+/// The error a [`ResponseFuture`] resolves to when its request was cancelled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("request was cancelled")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Cancelled {}
+
+/// A slot for a single response that the requester can await or cancel.
+///
+/// [`prepare`](ResponseSlot::prepare) hands out a [`Responder`] for the responder, and a
+/// [`ResponseFuture`] and a [`CancelHandle`] for the requester. It borrows the slot mutably,
+/// so there is only ever one set of handles, and the future's waker is never displaced by
+/// another future's. Cancelling closes the slot with a sentinel, so a response that arrives afterwards is dropped instead
+/// of being delivered to a requester that has gone away. A response already delivered but
+/// not yet awaited is dropped by the cancellation as well.
+///
+/// The slot is one-shot: once the response is taken or the request cancelled, it stays
+/// closed.
+pub struct ResponseSlot<T> {
+    inner: AtomicPtr<T>,
+    waker: AtomicWaker,
+    _phantom: PhantomData<Option<Box<T>>>,
+}
+
+// Safety: the response is delivered exactly once: `Responder` moves a `Box<T>` in and the
+// `ResponseFuture` or a cancellation moves it out, so a `T` crosses from the responder to the
+// requester but is never borrowed by both; the `AtomicWaker` is itself `Send` and `Sync`
+unsafe impl<T: Send> Send for ResponseSlot<T> {}
+unsafe impl<T: Send> Sync for ResponseSlot<T> {}
+
+impl<T> Default for ResponseSlot<T> {
+    /// Creates an open `ResponseSlot<T>` awaiting its response.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ResponseSlot<T> {
+    /// Creates an open `ResponseSlot` awaiting its response.
+    pub fn new() -> Self {
+        Self {
+            inner: AtomicPtr::new(ptr::null_mut()),
+            waker: AtomicWaker::new(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns the responder's handle, and the requester's handles to cancel the request and
+    /// to await the response.
+    ///
+    /// ```
+    /// # use atomic_slot::ResponseSlot;
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let mut slot = ResponseSlot::new();
+    /// let (responder, _cancel, response) = slot.prepare();
+    /// assert!(responder.respond(Box::new("pong")));
+    /// assert_eq!(*response.await.unwrap(), "pong");
+    /// # }
+    /// ```
+    ///
+    /// The handles borrow the slot mutably, so a second future cannot wait on it at the same
+    /// time:
+    ///
+    /// ```compile_fail
+    /// # use atomic_slot::ResponseSlot;
+    /// let mut slot = ResponseSlot::<i32>::new();
+    /// let (_, _, first) = slot.prepare();
+    /// let (_, _, second) = slot.prepare();
+    /// drop((first, second));
+    /// ```
+    pub fn prepare(&mut self) -> (Responder<'_, T>, CancelHandle<'_, T>, ResponseFuture<'_, T>) {
+        let slot = &*self;
+        (
+            Responder { slot },
+            CancelHandle { slot },
+            ResponseFuture { slot },
+        )
+    }
+
+    /// Delivers `value` as the response, returning whether it was delivered.
+    fn respond(&self, value: Box<T>) -> bool {
+        let raw = Box::into_raw(value);
+        match self
+            .inner
+            .compare_exchange(ptr::null_mut(), raw, Ordering::AcqRel, Ordering::Relaxed)
+        {
+            Ok(_) => {
+                self.waker.wake();
+                true
+            }
+            Err(_) => {
+                // Safety: `raw` came from `Box::into_raw` above and was not installed
+                drop(unsafe { Box::from_raw(raw) });
+                false
+            }
+        }
+    }
+
+    /// Closes the slot, dropping any undelivered response, and wakes the requester.
+    fn close(&self) {
+        let prev = self.inner.swap(closed(), Ordering::AcqRel);
+        // Safety: anything but null and the sentinel comes from `Box::into_raw`
+        drop(unsafe { into_response(prev) });
+        self.waker.wake();
+    }
+}
+
+impl<T> Drop for ResponseSlot<T> {
+    fn drop(&mut self) {
+        // Safety: anything but null and the sentinel comes from `Box::into_raw`
+        drop(unsafe { into_response(with_ptr_mut(&mut self.inner, |ptr| *ptr)) });
+    }
+}
+
+/// Answers a request made through [`ResponseSlot::prepare`].
+pub struct Responder<'a, T> {
+    slot: &'a ResponseSlot<T>,
+}
+
+impl<T> Responder<'_, T> {
+    /// Delivers `value` as the response, returning whether it was delivered.
+    ///
+    /// If the request was cancelled or already answered, `value` is dropped and `false` is
+    /// returned. Uses release ordering to publish the value.
+    pub fn respond(self, value: Box<T>) -> bool {
+        self.slot.respond(value)
+    }
+}
+
+/// Cancels a request made through [`ResponseSlot::prepare`].
+pub struct CancelHandle<'a, T> {
+    slot: &'a ResponseSlot<T>,
+}
+
+impl<T> CancelHandle<'_, T> {
+    /// Cancels the request: the [`ResponseFuture`] resolves to [`Cancelled`], and any
+    /// response, already delivered or arriving later, is dropped.
+    pub fn cancel(self) {
+        self.slot.close();
+    }
+}
+
+/// Resolves to the response delivered to a [`ResponseSlot`], or to [`Cancelled`].
+#[must_use = "futures do nothing unless polled"]
+pub struct ResponseFuture<'a, T> {
+    slot: &'a ResponseSlot<T>,
+}
+
+impl<T> Future for ResponseFuture<'_, T> {
+    type Output = Result<Box<T>, Cancelled>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let slot = self.slot;
+        // Registering first ensures a response or cancellation after the load wakes us
+        slot.waker.register(cx.waker());
+        let mut current = slot.inner.load(Ordering::Acquire);
+        loop {
+            if current.is_null() {
+                return Poll::Pending;
+            }
+            if current == closed() {
+                return Poll::Ready(Err(Cancelled));
+            }
+            match slot.inner.compare_exchange_weak(
+                current,
+                closed(),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                // Safety: the exchange made us the only owner of the response
+                Ok(_) => return Poll::Ready(Ok(unsafe { Box::from_raw(current) })),
+                Err(actual) => current = actual,
+            }
+        }
+    }
+}
+
+/// # Safety
+///
+/// `ptr` must be null, the closed sentinel, or come from `Box::into_raw` and not be owned
+/// elsewhere.
+unsafe fn into_response<T>(ptr: *mut T) -> Option<Box<T>> {
+    if ptr.is_null() || ptr == closed() {
+        None
+    } else {
+        // Safety: guaranteed by the caller
+        Some(unsafe { Box::from_raw(ptr) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn cancel_before_respond_drops_value() {
+        let mut slot = ResponseSlot::new();
+        let (responder, cancel, response) = slot.prepare();
+        cancel.cancel();
+
+        let value = Arc::new(());
+        assert!(!responder.respond(Box::new(value.clone())));
+        assert_eq!(Arc::strong_count(&value), 1);
+        assert_eq!(response.await.unwrap_err(), Cancelled);
+    }
+
+    #[tokio::test]
+    async fn response_wakes_pending_requester() {
+        let mut slot = ResponseSlot::new();
+        let (responder, _cancel, response) = slot.prepare();
+        // The response future is polled, and left pending, before the responder answers
+        let respond = async move {
+            tokio::task::yield_now().await;
+            assert!(responder.respond(Box::new(42)));
+        };
+        let ((), response) = tokio::join!(respond, response);
+        assert_eq!(*response.unwrap(), 42);
+
+        // The slot is one-shot: later handles find it closed
+        let (responder, _cancel, response) = slot.prepare();
+        assert!(!responder.respond(Box::new(43)));
+        assert_eq!(response.await.unwrap_err(), Cancelled);
+    }
+}
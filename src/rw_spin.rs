@@ -0,0 +1,200 @@
+//! A slot whose value can be read in place while a writer replaces it.
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+
+use core::marker::PhantomData;
+use core::ptr;
+
+use crate::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+use crate::sync::spin_loop;
+use crate::sync::with_ptr_mut;
+
+/// Set in [`RwSpinSlot`]'s state while a writer holds it; the bits above count readers.
+const WRITING: usize = 1;
+/// One registered reader in [`RwSpinSlot`]'s state.
+const READER: usize = 2;
+
+/// A slot for read-mostly values, such as configuration, that readers access in place.
+///
+/// [`read`](RwSpinSlot::read) borrows the current value without taking ownership or touching
+/// a reference count; [`write`](RwSpinSlot::write) replaces it. It is a spinning reader-writer
+/// lock rather than an optimistic seqlock: readers and writers share a single state word
+/// holding a writer flag and a reader count, so every read is a compare-and-swap on the word
+/// the writer uses. A reader registers itself only while no writer holds the flag, and a
+/// writer frees the old value only once every registered reader is done, so readers never
+/// observe a torn or freed value. For plain-old-data values, `ByteSlot` from the `bytemuck`
+/// feature is a real seqlock whose reads do not write to shared memory.
+///
+/// Readers spin while a write is in progress, and a write spins until the readers that
+/// registered before it are done, so keep the read closures short. Concurrent writers are
+/// safe but serialize. A panic in a read closure or in a write's constructor releases the
+/// slot as it unwinds.
+pub struct RwSpinSlot<T> {
+    state: AtomicUsize,
+    ptr: AtomicPtr<T>,
+    _phantom: PhantomData<Option<Box<T>>>,
+}
+
+// Safety: values are sent to the writing thread to be dropped and shared with readers
+unsafe impl<T: Send> Send for RwSpinSlot<T> {}
+unsafe impl<T: Send + Sync> Sync for RwSpinSlot<T> {}
+
+impl<T> Default for RwSpinSlot<T> {
+    /// Creates an empty `RwSpinSlot<T>`.
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<T> RwSpinSlot<T> {
+    /// Creates a new `RwSpinSlot` containing `value`.
+    pub fn new(value: Box<T>) -> Self {
+        Self::with_ptr(Box::into_raw(value))
+    }
+
+    /// Creates an empty `RwSpinSlot`.
+    pub fn empty() -> Self {
+        Self::with_ptr(ptr::null_mut())
+    }
+
+    fn with_ptr(ptr: *mut T) -> Self {
+        Self {
+            state: AtomicUsize::new(0),
+            ptr: AtomicPtr::new(ptr),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Runs `f` on the current value, returning `None` if the slot is empty.
+    ///
+    /// Spins while a write is in progress.
+    ///
+    /// ```
+    /// # use atomic_slot::RwSpinSlot;
+    /// let config = RwSpinSlot::new(Box::new((8080, "localhost")));
+    /// assert_eq!(config.read(|(port, _)| *port), Some(8080));
+    /// ```
+    pub fn read<R>(&self, f: impl FnOnce(&T) -> R) -> Option<R> {
+        self.lock(READER);
+        let _guard = Unlock {
+            state: &self.state,
+            held: READER,
+        };
+        // Safety: a writer does not free a value while a reader is registered
+        unsafe { self.ptr.load(Ordering::Acquire).as_ref() }.map(f)
+    }
+
+    /// Replaces the value, waiting for in-progress reads of the old value before dropping it.
+    ///
+    /// ```
+    /// # use atomic_slot::RwSpinSlot;
+    /// let config = RwSpinSlot::new(Box::new(1));
+    /// config.write(Box::new(2));
+    /// assert_eq!(config.read(|v| *v), Some(2));
+    /// ```
+    pub fn write(&self, value: Box<T>) {
+        self.write_with(|_| value);
+    }
+
+    /// Replaces the value with one built from the current value while holding the write
+    /// side of the lock, so concurrent writers see each other's results in order.
+    ///
+    /// If `make` panics, the value is left as it was.
+    pub(crate) fn write_with(&self, make: impl FnOnce(Option<&T>) -> Box<T>) {
+        self.lock(WRITING);
+        let guard = Unlock {
+            state: &self.state,
+            held: WRITING,
+        };
+
+        // Safety: only writers free values, and this one holds the write side of the lock
+        let value = make(unsafe { self.ptr.load(Ordering::Acquire).as_ref() });
+        let old = self.ptr.swap(Box::into_raw(value), Ordering::AcqRel);
+        // No new reader can register, so wait for the ones that may still see `old`
+        while self.state.load(Ordering::Acquire) != WRITING {
+            spin_loop();
+        }
+        drop(guard);
+
+        if !old.is_null() {
+            // Safety: `old` came from `Box::into_raw` and no reader can still see it
+            drop(unsafe { Box::from_raw(old) });
+        }
+    }
+
+    /// Spins until no writer holds the slot, then adds `held` to the state.
+    fn lock(&self, held: usize) {
+        let mut state = self.state.load(Ordering::Relaxed);
+        loop {
+            if state & WRITING != 0 {
+                spin_loop();
+                state = self.state.load(Ordering::Relaxed);
+                continue;
+            }
+            match self.state.compare_exchange_weak(
+                state,
+                state + held,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return,
+                Err(actual) => state = actual,
+            }
+        }
+    }
+}
+
+/// Releases a read or write registration, including while unwinding from a panic.
+struct Unlock<'a> {
+    state: &'a AtomicUsize,
+    held: usize,
+}
+
+impl Drop for Unlock<'_> {
+    fn drop(&mut self) {
+        self.state.fetch_sub(self.held, Ordering::Release);
+    }
+}
+
+impl<T> Drop for RwSpinSlot<T> {
+    fn drop(&mut self) {
+        let ptr = with_ptr_mut(&mut self.ptr, |ptr| *ptr);
+        if !ptr.is_null() {
+            // Safety: `ptr` came from `Box::into_raw` and the slot is being destroyed
+            drop(unsafe { Box::from_raw(ptr) });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::panic::{AssertUnwindSafe, catch_unwind};
+
+    #[test]
+    fn panicking_read_releases_the_slot() {
+        let slot = RwSpinSlot::new(Box::new(1));
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            slot.read(|_| panic!("reader failed"));
+        }));
+        assert!(result.is_err());
+
+        // Would spin forever if the reader were still registered
+        slot.write(Box::new(2));
+        assert_eq!(slot.read(|v| *v), Some(2));
+    }
+
+    #[test]
+    fn panicking_write_keeps_the_old_value() {
+        let slot = RwSpinSlot::new(Box::new(1));
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            slot.write_with(|_| panic!("constructor failed"));
+        }));
+        assert!(result.is_err());
+
+        assert_eq!(slot.read(|v| *v), Some(1));
+        slot.write(Box::new(3));
+        assert_eq!(slot.read(|v| *v), Some(3));
+    }
+}
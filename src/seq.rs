@@ -0,0 +1,95 @@
+//! A slot that numbers the values stored into it.
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+
+use crate::AtomicSlot;
+use crate::sync::atomic::{AtomicU64, Ordering};
+
+struct Node<T> {
+    seq: u64,
+    value: Box<T>,
+}
+
+/// A slot like [`AtomicSlot`] that tags each stored value with a sequence number.
+///
+/// Stores are numbered 1, 2, 3, ... and [`take`](SeqSlot::take) returns the number along with
+/// the value, so a consumer that remembers the last number it took can tell how many values
+/// were overwritten before it got to them.
+///
+/// The number travels with the value in a small node allocated by each store. Numbers are
+/// handed out when a store begins, so two racing stores may land in either order. The counter
+/// is a `u64` that wraps after 2<sup>64</sup> stores, which is unreachable in practice.
+pub struct SeqSlot<T> {
+    slot: AtomicSlot<Node<T>>,
+    next: AtomicU64,
+}
+
+impl<T> Default for SeqSlot<T> {
+    /// Creates an empty `SeqSlot<T>`.
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<T> SeqSlot<T> {
+    loom_const_fn! {
+        /// Creates an empty `SeqSlot` whose first store gets sequence number 1.
+        pub const fn empty() -> Self {
+            Self {
+                slot: AtomicSlot::empty(),
+                next: AtomicU64::new(1),
+            }
+        }
+    }
+
+    /// Stores `value` into the slot, returning its sequence number and dropping whatever
+    /// was there before.
+    pub fn store(&self, value: Box<T>) -> u64 {
+        let seq = self.next.fetch_add(1, Ordering::Relaxed);
+        self.slot.store(Some(Box::new(Node { seq, value })));
+        seq
+    }
+
+    /// Takes the current contents along with their sequence number, leaving the slot empty.
+    ///
+    /// ```
+    /// # use atomic_slot::SeqSlot;
+    /// let slot = SeqSlot::empty();
+    /// slot.store(Box::new("a"));
+    /// slot.store(Box::new("b"));
+    /// let (value, seq) = slot.take().unwrap();
+    /// assert_eq!((*value, seq), ("b", 2));
+    /// // Nothing was taken before, so one value was overwritten
+    /// let last_seen = 0;
+    /// assert_eq!(seq - last_seen - 1, 1);
+    /// ```
+    pub fn take(&self) -> Option<(Box<T>, u64)> {
+        self.slot.take().map(|node| (node.value, node.seq))
+    }
+
+    /// Returns `true` if the slot currently contains a value.
+    pub fn is_some(&self) -> bool {
+        self.slot.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn take_carries_latest_sequence() {
+        let slot = SeqSlot::empty();
+        assert!(slot.take().is_none());
+        for i in 1..=3 {
+            assert_eq!(slot.store(Box::new(i * 10)), i);
+        }
+        let (value, seq) = slot.take().unwrap();
+        assert_eq!((*value, seq), (30, 3));
+        assert!(!slot.is_some());
+
+        assert_eq!(slot.store(Box::new(40)), 4);
+        assert_eq!(slot.take().map(|(v, s)| (*v, s)), Some((40, 4)));
+    }
+}
@@ -0,0 +1,98 @@
+//! `serde` support, serializing a slot as an `Option<T>`.
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::AtomicSlot;
+
+impl<T: Serialize> Serialize for AtomicSlot<T> {
+    /// Serializes the contents as an `Option<T>`.
+    ///
+    /// Reading the value in place through `&self` would race with a thread taking and freeing
+    /// it, so the value is taken out for the duration of serialization and then put back.
+    /// Other threads see the slot as empty meanwhile, and a value they store in that window
+    /// wins over the restored one. The data is always serialized before any such replacement.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let value = self.take();
+        let result = value.as_deref().serialize(serializer);
+        if let Some(value) = value {
+            self.restore(value);
+        }
+        result
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for AtomicSlot<T> {
+    /// Deserializes an `Option<T>` into a new slot, empty for `None`.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match Option::<T>::deserialize(deserializer)? {
+            Some(value) => Self::new(Box::new(value)),
+            None => Self::empty(),
+        })
+    }
+}
+
+#[cfg(feature = "json")]
+impl AtomicSlot<Vec<u8>> {
+    /// Takes the JSON bytes out of the slot and deserializes them into a `U`.
+    ///
+    /// Returns `None` if the slot is empty. Malformed JSON, or JSON that does not match `U`,
+    /// is reported as a [`serde_json::Error`], in which case the bytes are dropped.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let slot = AtomicSlot::new(Box::new(br#"{"id": 7}"#.to_vec()));
+    /// let msg: serde_json::Value = slot.take_deserialize().unwrap().unwrap();
+    /// assert_eq!(msg["id"], 7);
+    /// ```
+    pub fn take_deserialize<U: serde::de::DeserializeOwned>(
+        &self,
+    ) -> Option<Result<U, serde_json::Error>> {
+        self.take().map(|bytes| serde_json::from_slice(&bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_some_and_none() {
+        let slot = AtomicSlot::new(Box::new(vec![1u8, 2]));
+        let json = serde_json::to_string(&slot).unwrap();
+        assert_eq!(json, "[1,2]");
+        assert_eq!(*slot.take().unwrap(), [1, 2]);
+
+        let restored: AtomicSlot<Vec<u8>> = serde_json::from_str(&json).unwrap();
+        assert_eq!(*restored.take().unwrap(), [1, 2]);
+
+        let json = serde_json::to_string(&AtomicSlot::<Vec<u8>>::empty()).unwrap();
+        assert_eq!(json, "null");
+        let restored: AtomicSlot<Vec<u8>> = serde_json::from_str(&json).unwrap();
+        assert!(restored.is_none());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn take_deserialize_reconstructs_message() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Message {
+            id: u32,
+            body: String,
+        }
+
+        let msg = Message {
+            id: 3,
+            body: String::from("hello"),
+        };
+        let slot = AtomicSlot::new(Box::new(serde_json::to_vec(&msg).unwrap()));
+        assert_eq!(slot.take_deserialize::<Message>().unwrap().unwrap(), msg);
+        assert!(slot.take_deserialize::<Message>().is_none());
+
+        slot.store(Some(Box::new(b"{\"id\": ".to_vec())));
+        assert!(slot.take_deserialize::<Message>().unwrap().is_err());
+        assert!(slot.is_none());
+    }
+}
@@ -0,0 +1,248 @@
+//! A slot that stores small values inline and boxes larger ones.
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+
+use core::marker::PhantomData;
+use core::mem::ManuallyDrop;
+use core::ptr;
+
+use crate::sync::atomic::{AtomicPtr, Ordering};
+use crate::sync::with_ptr_mut;
+
+/// Types whose every byte is initialized, so their bytes can be packed into an integer.
+///
+/// # Safety
+///
+/// `T` must not contain padding or any other uninitialized bytes.
+pub unsafe trait NoUninit {}
+
+macro_rules! impl_no_uninit {
+    ($($t:ty),*) => {
+        $(
+            // Safety: primitive types have no padding
+            unsafe impl NoUninit for $t {}
+        )*
+    };
+}
+
+impl_no_uninit!(
+    (),
+    bool,
+    char,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64
+);
+
+// Safety: arrays have no padding between elements that have none themselves
+unsafe impl<T: NoUninit, const N: usize> NoUninit for [T; N] {}
+
+const WORD: usize = size_of::<usize>();
+
+/// Byte of the inline representation marking that a value is present.
+const PRESENT_BYTE: usize = WORD - 1;
+
+/// A slot that transfers `T` by value, storing it inline in the atomic word when it fits and
+/// in a box otherwise.
+///
+/// `T` is stored inline when `size_of::<T>() < size_of::<usize>()` and
+/// `align_of::<T>() <= align_of::<usize>()`; the choice is made at compile time and can be
+/// checked with [`SmartSlot::INLINE`]. Inline values occupy the low bytes of the word and
+/// the last byte is reserved as a presence tag, so that an all-zero value can be told apart
+/// from an empty slot. Inline `swap`, `take` and `store` never allocate.
+///
+/// Because of that tag byte, a type as wide as the word is not stored inline: on 64-bit
+/// targets `u64`, `usize` and pointer-sized values are boxed, and on 32-bit targets `u32` is.
+///
+/// Larger types fall back to a `Box<T>`, just like [`AtomicSlot`](crate::AtomicSlot).
+///
+/// Packing a value into an integer requires all of its bytes to be initialized, hence the
+/// [`NoUninit`] bound. Non-`NoUninit` types can use `AtomicSlot` directly.
+pub struct SmartSlot<T: NoUninit> {
+    word: AtomicPtr<()>,
+    _phantom: PhantomData<Option<Box<T>>>,
+}
+
+// Safety: the word holds either raw bytes of an inline `T` or a `Box<T>` pointer, and every
+// `&self` method hands the value out by value (copied out of the word or unboxed), never as a
+// `&T`, so sharing the slot only ever moves a `T` from one thread to another
+unsafe impl<T: NoUninit + Send> Send for SmartSlot<T> {}
+unsafe impl<T: NoUninit + Send> Sync for SmartSlot<T> {}
+
+/// The name [`SmartSlot`] goes by when used for its inline storage of small values.
+///
+/// ```
+/// # use atomic_slot::SmallAtomicSlot;
+/// let slot = SmallAtomicSlot::new(7u16);
+/// assert_eq!(slot.take(), Some(7));
+/// ```
+pub type SmallAtomicSlot<T> = SmartSlot<T>;
+
+impl<T: NoUninit> Default for SmartSlot<T> {
+    /// Creates an empty `SmartSlot<T>`.
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<T: NoUninit> SmartSlot<T> {
+    /// Whether values of `T` are stored inline rather than boxed.
+    ///
+    /// ```
+    /// # use atomic_slot::SmartSlot;
+    /// assert!(SmartSlot::<u16>::INLINE);
+    /// assert!(!SmartSlot::<[u64; 4]>::INLINE);
+    /// ```
+    pub const INLINE: bool = size_of::<T>() < WORD && align_of::<T>() <= align_of::<usize>();
+
+    /// Creates a new `SmartSlot` containing `value`.
+    pub fn new(value: T) -> Self {
+        Self {
+            word: AtomicPtr::new(Self::pack(value)),
+            _phantom: PhantomData,
+        }
+    }
+
+    loom_const_fn! {
+        /// Creates an empty `SmartSlot`.
+        pub const fn empty() -> Self {
+            Self {
+                word: AtomicPtr::new(ptr::null_mut()),
+                _phantom: PhantomData,
+            }
+        }
+    }
+
+    /// Atomically swaps out the current contents for `value`, returning the old contents.
+    ///
+    /// Uses acquire–release ordering.
+    ///
+    /// ```
+    /// # use atomic_slot::SmartSlot;
+    /// let slot = SmartSlot::new(1u32);
+    /// assert_eq!(slot.swap(Some(2)), Some(1));
+    /// ```
+    pub fn swap(&self, value: Option<T>) -> Option<T> {
+        let raw = value.map_or(ptr::null_mut(), Self::pack);
+        let prev = self.word.swap(raw, Ordering::AcqRel);
+        // Safety: the word was produced by `pack` and is no longer in the slot
+        unsafe { Self::unpack(prev) }
+    }
+
+    /// Takes the current contents, leaving the slot empty.
+    ///
+    /// Uses acquire–release ordering.
+    ///
+    /// ```
+    /// # use atomic_slot::SmartSlot;
+    /// let slot = SmartSlot::new(0u8);
+    /// assert_eq!(slot.take(), Some(0));
+    /// assert_eq!(slot.take(), None);
+    /// ```
+    pub fn take(&self) -> Option<T> {
+        self.swap(None)
+    }
+
+    /// Stores `value` into the slot, dropping whatever was there before.
+    ///
+    /// Uses acquire–release ordering.
+    pub fn store(&self, value: Option<T>) {
+        let _ = self.swap(value);
+    }
+
+    /// Returns `true` if the slot currently contains a value. Uses acquire ordering.
+    pub fn is_some(&self) -> bool {
+        !self.is_none()
+    }
+
+    /// Returns `true` if the slot is empty. Uses acquire ordering.
+    pub fn is_none(&self) -> bool {
+        self.word.load(Ordering::Acquire).is_null()
+    }
+
+    fn pack(value: T) -> *mut () {
+        if Self::INLINE {
+            let value = ManuallyDrop::new(value);
+            let mut bytes = [0u8; WORD];
+            // Safety: `T` fits in the low bytes and all of its bytes are initialized
+            unsafe {
+                ptr::copy_nonoverlapping(
+                    (&*value as *const T).cast::<u8>(),
+                    bytes.as_mut_ptr(),
+                    size_of::<T>(),
+                );
+            }
+            bytes[PRESENT_BYTE] = 1;
+            ptr::without_provenance_mut(usize::from_ne_bytes(bytes))
+        } else {
+            Box::into_raw(Box::new(value)).cast()
+        }
+    }
+
+    /// # Safety
+    ///
+    /// `word` must be null or produced by `pack` and not be owned elsewhere.
+    unsafe fn unpack(word: *mut ()) -> Option<T> {
+        if word.is_null() {
+            None
+        } else if Self::INLINE {
+            let bytes = word.addr().to_ne_bytes();
+            // Safety: the low bytes hold a `T` written by `pack`
+            Some(unsafe { ptr::read_unaligned(bytes.as_ptr().cast::<T>()) })
+        } else {
+            // Safety: boxed words come from `Box::into_raw` in `pack`
+            Some(*unsafe { Box::from_raw(word.cast::<T>()) })
+        }
+    }
+}
+
+impl<T: NoUninit> Drop for SmartSlot<T> {
+    fn drop(&mut self) {
+        // Safety: the word was produced by `pack` and the slot is being destroyed
+        drop(unsafe { Self::unpack(with_ptr_mut(&mut self.word, |ptr| *ptr)) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_values_are_inline() {
+        const { assert!(SmartSlot::<u32>::INLINE) };
+        let slot = SmartSlot::empty();
+        assert!(slot.is_none());
+        // An all-zero value is still distinguishable from an empty slot
+        slot.store(Some(0u32));
+        assert!(slot.is_some());
+        assert_eq!(slot.swap(Some(u32::MAX)), Some(0));
+        assert_eq!(slot.take(), Some(u32::MAX));
+        assert_eq!(slot.take(), None);
+
+        let slot = SmartSlot::new([1u8, 2, 3]);
+        assert_eq!(slot.take(), Some([1, 2, 3]));
+    }
+
+    #[test]
+    fn large_values_are_boxed() {
+        const { assert!(!SmartSlot::<[u64; 8]>::INLINE) };
+        const { assert!(!SmartSlot::<usize>::INLINE) };
+        let slot = SmartSlot::new([7u64; 8]);
+        assert!(slot.is_some());
+        assert_eq!(slot.swap(Some([9; 8])), Some([7; 8]));
+        assert_eq!(slot.take(), Some([9; 8]));
+        assert!(slot.is_none());
+    }
+}
@@ -0,0 +1,117 @@
+//! A slot whose contents can be read in place under a short spinlock.
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+
+use core::ops::Deref;
+
+use crate::AtomicSlot;
+use crate::sync::atomic::{AtomicBool, Ordering};
+
+/// An [`AtomicSlot`] that supports reading its value in place with [`read_locked`].
+///
+/// Dereferences to the underlying [`AtomicSlot`], so all of its methods are available.
+///
+/// Reads take the value out of the slot, run the closure and put the value back. A per-slot
+/// spinlock serializes readers so that concurrent readers wait for each other instead of
+/// finding the slot empty. Readers spin while another read is in progress, so keep the
+/// closures short; under heavy read contention prefer an approach that does not serialize.
+///
+/// Other users of the slot are not excluded: a concurrent `take` during a read sees an empty
+/// slot, and a value stored during a read wins over the one being read, which is dropped.
+///
+/// [`read_locked`]: SpinReadSlot::read_locked
+pub struct SpinReadSlot<T> {
+    slot: AtomicSlot<T>,
+    read_lock: AtomicBool,
+}
+
+impl<T> Default for SpinReadSlot<T> {
+    /// Creates an empty `SpinReadSlot<T>`.
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<T> SpinReadSlot<T> {
+    /// Creates a new `SpinReadSlot` containing `value`.
+    pub fn new(value: Box<T>) -> Self {
+        Self {
+            slot: AtomicSlot::new(value),
+            read_lock: AtomicBool::new(false),
+        }
+    }
+
+    /// Creates an empty `SpinReadSlot`.
+    pub fn empty() -> Self {
+        Self {
+            slot: AtomicSlot::empty(),
+            read_lock: AtomicBool::new(false),
+        }
+    }
+
+    /// Runs `f` on the current value, returning `None` if the slot is empty.
+    ///
+    /// ```
+    /// # use atomic_slot::SpinReadSlot;
+    /// let slot = SpinReadSlot::new(Box::new(String::from("config")));
+    /// assert_eq!(slot.read_locked(|s| s.len()), Some(6));
+    /// assert!(slot.is_some());
+    /// ```
+    pub fn read_locked<R>(&self, f: impl FnOnce(&T) -> R) -> Option<R> {
+        while self
+            .read_lock
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            crate::sync::spin_loop();
+        }
+        let guard = Restore {
+            owner: self,
+            value: self.slot.take(),
+        };
+        guard.value.as_deref().map(f)
+    }
+}
+
+/// Puts the value back and releases the read lock, including while unwinding from a panic.
+struct Restore<'a, T> {
+    owner: &'a SpinReadSlot<T>,
+    value: Option<Box<T>>,
+}
+
+impl<T> Drop for Restore<'_, T> {
+    fn drop(&mut self) {
+        if let Some(value) = self.value.take() {
+            self.owner.slot.restore(value);
+        }
+        self.owner.read_lock.store(false, Ordering::Release);
+    }
+}
+
+impl<T> Deref for SpinReadSlot<T> {
+    type Target = AtomicSlot<T>;
+
+    fn deref(&self) -> &AtomicSlot<T> {
+        &self.slot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::panic::{AssertUnwindSafe, catch_unwind};
+
+    #[test]
+    fn panicking_read_restores_the_value_and_unlocks() {
+        let slot = SpinReadSlot::new(Box::new(1));
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            slot.read_locked(|_| panic!("reader failed"));
+        }));
+        assert!(result.is_err());
+
+        // Would spin forever if the lock were still held
+        assert_eq!(slot.read_locked(|v| *v), Some(1));
+        assert_eq!(*slot.take().unwrap(), 1);
+    }
+}
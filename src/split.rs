@@ -0,0 +1,144 @@
+//! Producer and consumer halves of a shared slot.
+
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, sync::Arc};
+#[cfg(feature = "std")]
+use std::sync::Arc;
+
+use core::cell::Cell;
+use core::marker::PhantomData;
+
+use crate::AtomicSlot;
+
+impl<T> AtomicSlot<T> {
+    /// Splits the slot into a [`Producer`] that can only fill it and a [`Consumer`] that can
+    /// only empty it.
+    ///
+    /// Both halves share the slot through an `Arc`. They are `Send` when `T` is, but neither
+    /// `Sync` nor `Clone`, so each half is owned by one thread at a time and the
+    /// single-producer/single-consumer discipline is enforced by the type system.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlot;
+    /// let (producer, consumer) = AtomicSlot::empty().split();
+    /// std::thread::spawn(move || producer.store(Box::new(1)))
+    ///     .join()
+    ///     .unwrap();
+    /// assert_eq!(*consumer.take().unwrap(), 1);
+    /// ```
+    ///
+    /// A producer cannot take:
+    ///
+    /// ```compile_fail
+    /// # use atomic_slot::AtomicSlot;
+    /// let (producer, _consumer) = AtomicSlot::<i32>::empty().split();
+    /// producer.take();
+    /// ```
+    ///
+    /// a consumer cannot store:
+    ///
+    /// ```compile_fail
+    /// # use atomic_slot::AtomicSlot;
+    /// let (_producer, consumer) = AtomicSlot::empty().split();
+    /// consumer.store(Box::new(1));
+    /// ```
+    ///
+    /// and a half cannot be shared between threads by reference:
+    ///
+    /// ```compile_fail
+    /// # use atomic_slot::AtomicSlot;
+    /// fn assert_sync<S: Sync>(_: &S) {}
+    /// let (producer, _consumer) = AtomicSlot::<i32>::empty().split();
+    /// assert_sync(&producer);
+    /// ```
+    pub fn split(self) -> (Producer<T>, Consumer<T>) {
+        let slot = Arc::new(self);
+        let producer = Producer {
+            slot: slot.clone(),
+            _unsync: PhantomData,
+        };
+        let consumer = Consumer {
+            slot,
+            _unsync: PhantomData,
+        };
+        (producer, consumer)
+    }
+}
+
+/// The filling half of a slot, created by [`AtomicSlot::split`].
+pub struct Producer<T> {
+    slot: Arc<AtomicSlot<T>>,
+    /// Keeps the half `!Sync`, so only its owning thread can produce.
+    _unsync: PhantomData<Cell<()>>,
+}
+
+impl<T> Producer<T> {
+    /// Stores `value` into the slot, dropping whatever was there before.
+    pub fn store(&self, value: Box<T>) {
+        self.slot.store(Some(value));
+    }
+
+    /// Stores `value` only if the slot is empty, handing it back otherwise.
+    #[doc(alias = "try_store")]
+    pub fn store_if_empty(&self, value: Box<T>) -> Result<(), Box<T>> {
+        self.slot.try_store(value)
+    }
+
+    /// Returns `true` if the slot currently contains a value.
+    pub fn is_some(&self) -> bool {
+        self.slot.is_some()
+    }
+}
+
+/// The emptying half of a slot, created by [`AtomicSlot::split`].
+pub struct Consumer<T> {
+    slot: Arc<AtomicSlot<T>>,
+    /// Keeps the half `!Sync`, so only its owning thread can consume.
+    _unsync: PhantomData<Cell<()>>,
+}
+
+impl<T> Consumer<T> {
+    /// Takes the current contents, leaving the slot empty.
+    pub fn take(&self) -> Option<Box<T>> {
+        self.slot.take()
+    }
+
+    /// Returns `true` if the slot currently contains a value.
+    pub fn is_some(&self) -> bool {
+        self.slot.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn assert_send<S: Send>(_: &S) {}
+
+    #[test]
+    fn halves_coordinate_over_shared_slot() {
+        let (producer, consumer) = AtomicSlot::<String>::empty().split();
+        assert_send(&producer);
+        assert_send(&consumer);
+
+        let producer = thread::spawn(move || {
+            producer.store(Box::new(String::from("first")));
+            let rejected = producer.store_if_empty(Box::new(String::from("second")));
+            assert_eq!(*rejected.unwrap_err(), "second");
+            producer
+        })
+        .join()
+        .unwrap();
+
+        assert!(consumer.is_some());
+        assert_eq!(*consumer.take().unwrap(), "first");
+        assert!(!producer.is_some());
+        assert!(
+            producer
+                .store_if_empty(Box::new(String::from("third")))
+                .is_ok()
+        );
+        assert_eq!(*consumer.take().unwrap(), "third");
+    }
+}
@@ -0,0 +1,36 @@
+//! Synchronization primitives, swapped for their loom counterparts under `cfg(loom)`.
+//!
+//! With the `portable-atomic` feature, every atomic comes from `portable_atomic` instead,
+//! which emulates compare-and-swap and 64-bit atomics on targets that lack them. Under
+//! `cfg(loom)` loom's atomics take precedence, so every type in the crate is modeled.
+
+#[cfg(not(loom))]
+pub(crate) use core::hint::spin_loop;
+#[cfg(loom)]
+pub(crate) use loom::hint::spin_loop;
+
+pub(crate) use atomic::{AtomicPtr, Ordering};
+
+/// The atomics used throughout the crate.
+pub(crate) mod atomic {
+    #[cfg(not(any(loom, feature = "portable-atomic")))]
+    pub(crate) use core::sync::atomic::*;
+    #[cfg(loom)]
+    pub(crate) use loom::sync::atomic::*;
+    #[cfg(all(not(loom), feature = "portable-atomic"))]
+    pub(crate) use portable_atomic::*;
+}
+
+/// Gives `f` the pointer inside `ptr` through exclusive access, without an atomic operation.
+///
+/// Loom's atomics have no `get_mut`, only `with_mut`.
+pub(crate) fn with_ptr_mut<T, R>(ptr: &mut AtomicPtr<T>, f: impl FnOnce(&mut *mut T) -> R) -> R {
+    #[cfg(not(loom))]
+    {
+        f(ptr.get_mut())
+    }
+    #[cfg(loom)]
+    {
+        ptr.with_mut(f)
+    }
+}
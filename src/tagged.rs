@@ -0,0 +1,423 @@
+//! A slot that carries a few bits of state alongside its pointer.
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+
+use core::marker::PhantomData;
+use core::ptr;
+
+use crate::sync::atomic::{AtomicPtr, Ordering};
+use crate::sync::with_ptr_mut;
+
+/// A slot like [`AtomicSlot`](crate::AtomicSlot) with a small tag stored in the unused low bits
+/// of the pointer.
+///
+/// The tag is independent of the value: swapping values keeps it, and changing it keeps the
+/// value, so it can drive a state machine such as a "dirty" flag. It can hold numbers up to
+/// [`TaggedSlot::TAG_MASK`], which is `align_of::<T>() - 1` capped at `u8::MAX`; `T` must
+/// therefore be aligned to at least 2, which is checked at compile time.
+pub struct TaggedSlot<T> {
+    inner: AtomicPtr<T>,
+    _phantom: PhantomData<Option<Box<T>>>,
+}
+
+// Safety: the tag bits are plain data and the rest of the word is an owned `Box<T>` pointer;
+// `swap`, `take` and `store` move whole boxes while `tag` and `compare_exchange_tag` never
+// dereference the pointer, so through `&self` a `T` is only moved between threads
+unsafe impl<T: Send> Send for TaggedSlot<T> {}
+unsafe impl<T: Send> Sync for TaggedSlot<T> {}
+
+impl<T> Default for TaggedSlot<T> {
+    /// Creates an empty `TaggedSlot<T>` with tag 0.
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<T> TaggedSlot<T> {
+    /// The largest tag a `TaggedSlot<T>` can hold.
+    pub const TAG_MASK: u8 = {
+        assert!(
+            align_of::<T>() >= 2,
+            "TaggedSlot<T> requires align_of::<T>() >= 2"
+        );
+        if align_of::<T>() > 256 {
+            u8::MAX
+        } else {
+            (align_of::<T>() - 1) as u8
+        }
+    };
+
+    /// Creates a new `TaggedSlot` containing `value` with tag 0.
+    pub fn new(value: Box<T>) -> Self {
+        let _ = Self::TAG_MASK;
+        Self {
+            inner: AtomicPtr::new(Box::into_raw(value)),
+            _phantom: PhantomData,
+        }
+    }
+
+    loom_const_fn! {
+        /// Creates an empty `TaggedSlot` with tag 0.
+        pub const fn empty() -> Self {
+            let _ = Self::TAG_MASK;
+            Self {
+                inner: AtomicPtr::new(ptr::null_mut()),
+                _phantom: PhantomData,
+            }
+        }
+    }
+
+    /// Atomically swaps out the current contents for `value`, keeping the tag.
+    ///
+    /// Uses acquire–release ordering.
+    pub fn swap(&self, value: Option<Box<T>>) -> Option<Box<T>> {
+        let new = value.map_or(ptr::null_mut(), Box::into_raw);
+        let mut current = self.inner.load(Ordering::Relaxed);
+        loop {
+            let tagged = new.map_addr(|addr| addr | tag_of(current) as usize);
+            match self.inner.compare_exchange_weak(
+                current,
+                tagged,
+                Ordering::AcqRel,
+                Ordering::Relaxed,
+            ) {
+                // Safety: untagged non-null pointers in the slot come from `Box::into_raw`
+                Ok(prev) => return unsafe { from_tagged(prev) },
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    /// Takes the current contents, leaving the slot empty and the tag unchanged.
+    pub fn take(&self) -> Option<Box<T>> {
+        self.swap(None)
+    }
+
+    /// Stores `value` into the slot, dropping whatever was there before.
+    pub fn store(&self, value: Option<Box<T>>) {
+        let _ = self.swap(value);
+    }
+
+    /// Returns `true` if the slot currently contains a value. Uses acquire ordering.
+    pub fn is_some(&self) -> bool {
+        !untag(self.inner.load(Ordering::Acquire)).is_null()
+    }
+
+    /// Returns the current tag. Uses acquire ordering.
+    pub fn tag(&self) -> u8 {
+        tag_of(self.inner.load(Ordering::Acquire))
+    }
+
+    /// Sets the tag to `new_tag` if it is currently `current_tag`, leaving the value alone.
+    ///
+    /// Returns the previous tag on success and the actual tag on failure. A value swapped in
+    /// concurrently does not cause a spurious failure; the exchange is retried with the new
+    /// pointer. Uses acquire–release ordering.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `new_tag` is larger than [`TAG_MASK`](Self::TAG_MASK).
+    ///
+    /// ```
+    /// # use atomic_slot::TaggedSlot;
+    /// const DIRTY: u8 = 1;
+    /// let slot = TaggedSlot::new(Box::new(5u32));
+    /// assert_eq!(slot.compare_exchange_tag(0, DIRTY), Ok(0));
+    /// assert_eq!(slot.compare_exchange_tag(0, DIRTY), Err(DIRTY));
+    /// assert_eq!(*slot.take().unwrap(), 5);
+    /// assert_eq!(slot.tag(), DIRTY);
+    /// ```
+    pub fn compare_exchange_tag(&self, current_tag: u8, new_tag: u8) -> Result<u8, u8> {
+        assert!(
+            new_tag & !Self::TAG_MASK == 0,
+            "tag {new_tag} does not fit in TaggedSlot::TAG_MASK"
+        );
+        let mut current = self.inner.load(Ordering::Acquire);
+        loop {
+            let tag = tag_of(current);
+            if tag != current_tag {
+                return Err(tag);
+            }
+            let retagged = untag(current).map_addr(|addr| addr | new_tag as usize);
+            match self.inner.compare_exchange_weak(
+                current,
+                retagged,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return Ok(tag),
+                Err(actual) => current = actual,
+            }
+        }
+    }
+}
+
+impl<T> Drop for TaggedSlot<T> {
+    fn drop(&mut self) {
+        // Safety: untagged non-null pointers in the slot come from `Box::into_raw`
+        drop(unsafe { from_tagged(with_ptr_mut(&mut self.inner, |ptr| *ptr)) });
+    }
+}
+
+/// A slot whose value carries a schema version in the unused low bits of its pointer.
+///
+/// Unlike the tag of a [`TaggedSlot`], the version belongs to the value: it is set together
+/// with each stored value and cleared when the value is taken, so a consumer can reject
+/// payloads from an incompatible producer with [`take_if_version`](Self::take_if_version).
+/// Versions can go up to [`VERSION_MASK`](Self::VERSION_MASK), which is `align_of::<T>() - 1`
+/// capped at `u8::MAX`; a `T` with `#[repr(align(256))]` can carry a full byte.
+pub struct TaggedVersionSlot<T> {
+    inner: AtomicPtr<T>,
+    _phantom: PhantomData<Option<Box<T>>>,
+}
+
+// Safety: the version bits travel with the value, and every `&self` method moves the whole
+// `Box<T>` out together with its version or only reads the version bits, so no `&T` is shared
+// and a `T` is only moved between threads
+unsafe impl<T: Send> Send for TaggedVersionSlot<T> {}
+unsafe impl<T: Send> Sync for TaggedVersionSlot<T> {}
+
+impl<T> Default for TaggedVersionSlot<T> {
+    /// Creates an empty `TaggedVersionSlot<T>`.
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+/// The error returned by [`TaggedVersionSlot::take_if_version`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VersionMismatch {
+    /// The version the caller asked for.
+    pub expected: u8,
+    /// The version of the value left in the slot, or `None` if the slot was empty.
+    pub actual: Option<u8>,
+}
+
+impl core::fmt::Display for VersionMismatch {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.actual {
+            Some(actual) => write!(f, "expected version {}, found {actual}", self.expected),
+            None => write!(f, "expected version {}, slot is empty", self.expected),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for VersionMismatch {}
+
+impl<T> TaggedVersionSlot<T> {
+    /// The largest version a `TaggedVersionSlot<T>` can hold.
+    pub const VERSION_MASK: u8 = TaggedSlot::<T>::TAG_MASK;
+
+    /// Creates a new `TaggedVersionSlot` containing `value` at `version`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `version` is larger than [`VERSION_MASK`](Self::VERSION_MASK).
+    pub fn new(value: Box<T>, version: u8) -> Self {
+        Self {
+            inner: AtomicPtr::new(Self::tagged(Some(value), version)),
+            _phantom: PhantomData,
+        }
+    }
+
+    loom_const_fn! {
+        /// Creates an empty `TaggedVersionSlot`.
+        pub const fn empty() -> Self {
+            let _ = Self::VERSION_MASK;
+            Self {
+                inner: AtomicPtr::new(ptr::null_mut()),
+                _phantom: PhantomData,
+            }
+        }
+    }
+
+    /// Atomically swaps out the current contents for `value` at `version`, returning the old
+    /// contents with their version.
+    ///
+    /// The version of an empty `value` is ignored. Uses acquire–release ordering.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `version` is larger than [`VERSION_MASK`](Self::VERSION_MASK).
+    pub fn swap(&self, value: Option<Box<T>>, version: u8) -> Option<(Box<T>, u8)> {
+        let prev = self
+            .inner
+            .swap(Self::tagged(value, version), Ordering::AcqRel);
+        let version = tag_of(prev);
+        // Safety: untagged non-null pointers in the slot come from `Box::into_raw`
+        unsafe { from_tagged(prev) }.map(|value| (value, version))
+    }
+
+    /// Stores `value` at `version`, dropping whatever was there before.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `version` is larger than [`VERSION_MASK`](Self::VERSION_MASK).
+    pub fn store(&self, value: Option<Box<T>>, version: u8) {
+        let _ = self.swap(value, version);
+    }
+
+    /// Takes the current contents with their version, leaving the slot empty.
+    pub fn take(&self) -> Option<(Box<T>, u8)> {
+        self.swap(None, 0)
+    }
+
+    /// Takes the value only if it was stored at version `expected`.
+    ///
+    /// On a mismatch the value is left in the slot for a consumer that understands it, and
+    /// the error carries the version found. The check and the take are a single atomic
+    /// exchange, so a value replaced concurrently is never taken under the old version. Uses
+    /// acquire–release ordering.
+    ///
+    /// ```
+    /// # use atomic_slot::TaggedVersionSlot;
+    /// let slot = TaggedVersionSlot::new(Box::new(7u32), 2);
+    /// let err = slot.take_if_version(1).unwrap_err();
+    /// assert_eq!(err.actual, Some(2));
+    /// assert_eq!(*slot.take_if_version(2).unwrap(), 7);
+    /// ```
+    pub fn take_if_version(&self, expected: u8) -> Result<Box<T>, VersionMismatch> {
+        let mut current = self.inner.load(Ordering::Acquire);
+        loop {
+            if current.is_null() {
+                return Err(VersionMismatch {
+                    expected,
+                    actual: None,
+                });
+            }
+            let version = tag_of(current);
+            if version != expected {
+                return Err(VersionMismatch {
+                    expected,
+                    actual: Some(version),
+                });
+            }
+            match self.inner.compare_exchange_weak(
+                current,
+                ptr::null_mut(),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                // Safety: untagged non-null pointers in the slot come from `Box::into_raw`,
+                // and the exchange moved ownership to us
+                Ok(prev) => return Ok(unsafe { from_tagged(prev) }.expect("checked non-null")),
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    /// Returns the version of the current value, or `None` if the slot is empty. Uses
+    /// acquire ordering.
+    pub fn version(&self) -> Option<u8> {
+        let current = self.inner.load(Ordering::Acquire);
+        (!current.is_null()).then(|| tag_of(current))
+    }
+
+    fn tagged(value: Option<Box<T>>, version: u8) -> *mut T {
+        assert!(
+            version & !Self::VERSION_MASK == 0,
+            "version {version} does not fit in TaggedVersionSlot::VERSION_MASK"
+        );
+        match value {
+            Some(value) => Box::into_raw(value).map_addr(|addr| addr | version as usize),
+            None => ptr::null_mut(),
+        }
+    }
+}
+
+impl<T> Drop for TaggedVersionSlot<T> {
+    fn drop(&mut self) {
+        // Safety: untagged non-null pointers in the slot come from `Box::into_raw`
+        drop(unsafe { from_tagged(with_ptr_mut(&mut self.inner, |ptr| *ptr)) });
+    }
+}
+
+/// Masks the tag out of `ptr`; the mask only ever covers bits below the alignment of `T`.
+fn tag_of<T>(ptr: *mut T) -> u8 {
+    (ptr.addr() & (align_of::<T>() - 1) & u8::MAX as usize) as u8
+}
+
+fn untag<T>(ptr: *mut T) -> *mut T {
+    ptr.map_addr(|addr| addr & !(tag_of(ptr) as usize))
+}
+
+/// # Safety
+///
+/// Once untagged, `ptr` must be null or come from `Box::into_raw` and not be owned elsewhere.
+unsafe fn from_tagged<T>(ptr: *mut T) -> Option<Box<T>> {
+    let ptr = untag(ptr);
+    if ptr.is_null() {
+        None
+    } else {
+        // Safety: guaranteed by the caller
+        Some(unsafe { Box::from_raw(ptr) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tag_and_value_are_independent() {
+        assert_eq!(TaggedSlot::<u32>::TAG_MASK, 3);
+        assert_eq!(TaggedSlot::<[u64; 64]>::TAG_MASK, 7);
+
+        let slot = TaggedSlot::<u64>::empty();
+        assert_eq!(slot.compare_exchange_tag(0, 7), Ok(0));
+        assert!(!slot.is_some());
+        slot.store(Some(Box::new(1)));
+        assert_eq!(slot.tag(), 7);
+        assert_eq!(*slot.swap(Some(Box::new(2))).unwrap(), 1);
+        assert_eq!(slot.compare_exchange_tag(7, 2), Ok(7));
+        assert_eq!(*slot.take().unwrap(), 2);
+        assert_eq!(slot.tag(), 2);
+    }
+
+    #[test]
+    fn matching_version_is_taken() {
+        let slot = TaggedVersionSlot::<u64>::empty();
+        assert_eq!(TaggedVersionSlot::<u64>::VERSION_MASK, 7);
+        assert_eq!(
+            slot.take_if_version(3),
+            Err(VersionMismatch {
+                expected: 3,
+                actual: None
+            })
+        );
+
+        slot.store(Some(Box::new(10)), 3);
+        assert_eq!(slot.version(), Some(3));
+        assert_eq!(*slot.take_if_version(3).unwrap(), 10);
+        assert_eq!(slot.version(), None);
+
+        slot.store(Some(Box::new(11)), 5);
+        let (old, version) = slot.swap(Some(Box::new(12)), 6).unwrap();
+        assert_eq!((*old, version), (11, 5));
+        assert_eq!(slot.take().map(|(v, version)| (*v, version)), Some((12, 6)));
+    }
+
+    #[test]
+    fn mismatching_version_is_left_in_place() {
+        let slot = TaggedVersionSlot::new(Box::new(1u32), 2);
+        let err = slot.take_if_version(1).unwrap_err();
+        assert_eq!(err.actual, Some(2));
+        assert_eq!(err.to_string(), "expected version 1, found 2");
+        assert_eq!(slot.version(), Some(2));
+        assert_eq!(*slot.take().unwrap().0, 1);
+    }
+
+    #[test]
+    #[should_panic = "does not fit"]
+    fn oversized_version_panics() {
+        TaggedVersionSlot::<u32>::empty().store(Some(Box::new(1)), 4);
+    }
+
+    #[test]
+    #[should_panic = "does not fit"]
+    fn oversized_tag_panics() {
+        let _ = TaggedSlot::<u16>::empty().compare_exchange_tag(0, 2);
+    }
+}
@@ -0,0 +1,97 @@
+//! A slot that measures how long values wait in it.
+
+use std::time::{Duration, Instant};
+
+use crate::AtomicSlot;
+
+struct Node<T> {
+    stored: Instant,
+    value: Box<T>,
+}
+
+/// A slot like [`AtomicSlot`] that reports how long each value sat in the slot, for
+/// measuring consumer lag.
+///
+/// Each store records an [`Instant`] in a small node allocated next to the value, so a
+/// `TimedSlot` costs one extra allocation per store and reads the clock on every store and
+/// every swap or take that finds a value.
+pub struct TimedSlot<T> {
+    slot: AtomicSlot<Node<T>>,
+}
+
+impl<T> Default for TimedSlot<T> {
+    /// Creates an empty `TimedSlot<T>`.
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<T> TimedSlot<T> {
+    loom_const_fn! {
+        /// Creates an empty `TimedSlot`.
+        pub const fn empty() -> Self {
+            Self {
+                slot: AtomicSlot::empty(),
+            }
+        }
+    }
+
+    /// Atomically swaps out the current contents for `value`, returning the old contents and
+    /// how long they occupied the slot.
+    ///
+    /// ```
+    /// # use atomic_slot::TimedSlot;
+    /// let slot = TimedSlot::empty();
+    /// assert!(slot.swap(Some(Box::new(1))).is_none());
+    /// let (old, waited) = slot.swap(Some(Box::new(2))).unwrap();
+    /// assert_eq!(*old, 1);
+    /// println!("value waited {waited:?}");
+    /// ```
+    pub fn swap(&self, value: Option<Box<T>>) -> Option<(Box<T>, Duration)> {
+        let node = value.map(|value| {
+            Box::new(Node {
+                stored: Instant::now(),
+                value,
+            })
+        });
+        self.slot
+            .swap(node)
+            .map(|node| (node.value, node.stored.elapsed()))
+    }
+
+    /// Takes the current contents along with how long they occupied the slot.
+    pub fn take(&self) -> Option<(Box<T>, Duration)> {
+        self.swap(None)
+    }
+
+    /// Stores `value` into the slot, dropping whatever was there before.
+    pub fn store(&self, value: Option<Box<T>>) {
+        let _ = self.swap(value);
+    }
+
+    /// Returns `true` if the slot currently contains a value.
+    pub fn is_some(&self) -> bool {
+        self.slot.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn reports_occupancy_duration() {
+        let slot = TimedSlot::empty();
+        slot.store(Some(Box::new("queued")));
+        thread::sleep(Duration::from_millis(20));
+
+        let (value, waited) = slot.swap(Some(Box::new("next"))).unwrap();
+        assert_eq!(*value, "queued");
+        assert!(waited >= Duration::from_millis(20));
+
+        let (value, _) = slot.take().unwrap();
+        assert_eq!(*value, "next");
+        assert!(!slot.is_some());
+    }
+}
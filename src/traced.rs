@@ -0,0 +1,161 @@
+//! A slot that reports values it drops without anyone taking them.
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+
+use crate::AtomicSlot;
+
+#[cfg(feature = "trace-drop")]
+mod entry {
+    use std::time::Instant;
+
+    /// A stored value along with when it was stored; logs if dropped while still holding it.
+    pub(super) struct Entry<T> {
+        value: Option<Box<T>>,
+        stored: Instant,
+    }
+
+    pub(super) fn wrap<T>(value: Box<T>) -> Box<Entry<T>> {
+        Box::new(Entry {
+            value: Some(value),
+            stored: Instant::now(),
+        })
+    }
+
+    #[allow(clippy::boxed_local, reason = "matches the untraced signature")]
+    pub(super) fn unwrap<T>(mut entry: Box<Entry<T>>) -> Box<T> {
+        entry
+            .value
+            .take()
+            .expect("entries hold a value until unwrapped")
+    }
+
+    impl<T> Drop for Entry<T> {
+        fn drop(&mut self) {
+            if let Some(value) = &self.value {
+                log::warn!(
+                    "TracedSlot dropped value at {:p} that was never taken, {:?} after it was stored",
+                    &**value,
+                    self.stored.elapsed()
+                );
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "trace-drop"))]
+mod entry {
+    #[cfg(not(feature = "std"))]
+    use alloc::boxed::Box;
+
+    pub(super) type Entry<T> = T;
+
+    pub(super) fn wrap<T>(value: Box<T>) -> Box<Entry<T>> {
+        value
+    }
+
+    pub(super) fn unwrap<T>(entry: Box<Entry<T>>) -> Box<T> {
+        entry
+    }
+}
+
+use entry::{Entry, unwrap, wrap};
+
+/// A slot like [`AtomicSlot`] that logs values it drops without anyone taking them, for
+/// finding values that were stored but never consumed.
+///
+/// With the `trace-drop` feature, a value that is overwritten by [`store`](TracedSlot::store)
+/// or still in the slot when it is dropped is reported through [`log::warn!`] with its address
+/// and how long it sat in the slot. Values handed back by `swap` or `take` are not reported.
+/// Tracing allocates a small record alongside each stored value.
+///
+/// Without the feature, `TracedSlot<T>` is a plain `AtomicSlot<T>` and costs nothing extra.
+pub struct TracedSlot<T> {
+    slot: AtomicSlot<Entry<T>>,
+}
+
+impl<T> Default for TracedSlot<T> {
+    /// Creates an empty `TracedSlot<T>`.
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<T> TracedSlot<T> {
+    /// Creates a new `TracedSlot` containing `value`.
+    pub fn new(value: Box<T>) -> Self {
+        Self {
+            slot: AtomicSlot::new(wrap(value)),
+        }
+    }
+
+    loom_const_fn! {
+        /// Creates an empty `TracedSlot`.
+        pub const fn empty() -> Self {
+            Self {
+                slot: AtomicSlot::empty(),
+            }
+        }
+    }
+
+    /// Atomically swaps out the current contents for `value`, returning the old contents.
+    pub fn swap(&self, value: Option<Box<T>>) -> Option<Box<T>> {
+        self.slot.swap(value.map(wrap)).map(unwrap)
+    }
+
+    /// Takes the current contents, leaving the slot empty.
+    pub fn take(&self) -> Option<Box<T>> {
+        self.slot.take().map(unwrap)
+    }
+
+    /// Stores `value` into the slot, dropping and reporting whatever was there before.
+    pub fn store(&self, value: Option<Box<T>>) {
+        self.slot.store(value.map(wrap));
+    }
+
+    /// Returns `true` if the slot currently contains a value.
+    pub fn is_some(&self) -> bool {
+        self.slot.is_some()
+    }
+}
+
+#[cfg(all(test, feature = "trace-drop"))]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    static LINES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    struct Capture;
+
+    impl log::Log for Capture {
+        fn enabled(&self, _: &log::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record<'_>) {
+            LINES.lock().unwrap().push(record.args().to_string());
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn logs_untaken_values() {
+        log::set_logger(&Capture).unwrap();
+        log::set_max_level(log::LevelFilter::Warn);
+
+        let first = Box::new(1);
+        let first_addr = format!("{:p}", &*first);
+        let slot = TracedSlot::new(first);
+        slot.store(Some(Box::new(2)));
+        assert_eq!(*slot.take().unwrap(), 2);
+        assert!(slot.swap(Some(Box::new(3))).is_none());
+        drop(slot);
+
+        let lines = LINES.lock().unwrap();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains(&first_addr));
+        assert!(lines.iter().all(|l| l.contains("never taken")));
+    }
+}
@@ -0,0 +1,130 @@
+//! A slot that records which thread last took its value.
+
+use std::sync::Mutex;
+use std::thread::{self, ThreadId};
+
+use crate::AtomicSlot;
+use crate::sync::atomic::{AtomicUsize, Ordering};
+
+/// An [`AtomicSlot`] that records the thread that last removed a value, and how many values
+/// have been removed.
+///
+/// Intended for diagnosing higher-level protocol bugs, such as two consumers both believing
+/// they processed the same value. The bookkeeping happens after the atomic operation, so
+/// under concurrent takes the recorded thread is one of the recent takers.
+pub struct DebugSlot<T> {
+    slot: AtomicSlot<T>,
+    last_taker: Mutex<Option<ThreadId>>,
+    takes: AtomicUsize,
+}
+
+impl<T> Default for DebugSlot<T> {
+    /// Creates an empty `DebugSlot<T>`.
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<T> DebugSlot<T> {
+    /// Creates a new `DebugSlot` containing `value`.
+    pub fn new(value: Box<T>) -> Self {
+        Self::from_slot(AtomicSlot::new(value))
+    }
+
+    /// Creates an empty `DebugSlot`.
+    pub fn empty() -> Self {
+        Self::from_slot(AtomicSlot::empty())
+    }
+
+    fn from_slot(slot: AtomicSlot<T>) -> Self {
+        Self {
+            slot,
+            last_taker: Mutex::new(None),
+            takes: AtomicUsize::new(0),
+        }
+    }
+
+    /// Atomically swaps out the current contents for `value`, returning the old contents.
+    ///
+    /// Removing a value this way is recorded like a [`take`](Self::take).
+    pub fn swap(&self, value: Option<Box<T>>) -> Option<Box<T>> {
+        let prev = self.slot.swap(value);
+        if prev.is_some() {
+            self.record_take();
+        }
+        prev
+    }
+
+    /// Takes the current contents, recording the calling thread if a value was taken.
+    ///
+    /// ```
+    /// # use atomic_slot::DebugSlot;
+    /// let slot = DebugSlot::new(Box::new(1));
+    /// assert!(slot.last_taker().is_none());
+    /// slot.take();
+    /// assert_eq!(slot.last_taker(), Some(std::thread::current().id()));
+    /// assert_eq!(slot.take_count(), 1);
+    /// ```
+    pub fn take(&self) -> Option<Box<T>> {
+        self.swap(None)
+    }
+
+    /// Stores `value` into the slot, dropping whatever was there before.
+    ///
+    /// Dropping a previous value is not recorded as a take.
+    pub fn store(&self, value: Option<Box<T>>) {
+        self.slot.store(value);
+    }
+
+    /// Returns `true` if the slot currently contains a value.
+    pub fn is_some(&self) -> bool {
+        self.slot.is_some()
+    }
+
+    /// Returns the thread that most recently took a value, if any.
+    pub fn last_taker(&self) -> Option<ThreadId> {
+        *self.last_taker.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Returns how many values have been taken from the slot.
+    pub fn take_count(&self) -> usize {
+        self.takes.load(Ordering::Acquire)
+    }
+
+    fn record_take(&self) {
+        *self.last_taker.lock().unwrap_or_else(|e| e.into_inner()) = Some(thread::current().id());
+        self.takes.fetch_add(1, Ordering::AcqRel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn last_taker_is_taking_thread() {
+        let slot = Arc::new(DebugSlot::new(Box::new(5)));
+        assert_eq!(slot.last_taker(), None);
+
+        let taker = {
+            let slot = slot.clone();
+            thread::spawn(move || {
+                assert_eq!(*slot.take().unwrap(), 5);
+                thread::current().id()
+            })
+        };
+        let taker_id = taker.join().unwrap();
+        assert_eq!(slot.last_taker(), Some(taker_id));
+        assert_eq!(slot.take_count(), 1);
+
+        // Taking from an empty slot changes nothing
+        assert!(slot.take().is_none());
+        assert_eq!(slot.last_taker(), Some(taker_id));
+
+        slot.store(Some(Box::new(6)));
+        assert_eq!(*slot.swap(None).unwrap(), 6);
+        assert_eq!(slot.last_taker(), Some(thread::current().id()));
+        assert_eq!(slot.take_count(), 2);
+    }
+}
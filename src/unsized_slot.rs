@@ -0,0 +1,136 @@
+//! A slot for unsized values such as slices and trait objects.
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+
+use crate::AtomicSlot;
+
+/// A slot like [`AtomicSlot`] for `Box<T>` where `T` may be unsized, such as `Box<[T]>`,
+/// `Box<str>` or `Box<dyn Trait>`.
+///
+/// A box of an unsized value is a fat pointer, which `AtomicPtr` cannot hold. Each stored
+/// box is therefore boxed once more and the slot holds the thin pointer to that outer box.
+/// Storing costs one extra small allocation; taking frees it and hands back the original box.
+///
+/// ```
+/// # use atomic_slot::AtomicSlotUnsized;
+/// let slot: AtomicSlotUnsized<str> = AtomicSlotUnsized::new("hello".into());
+/// assert_eq!(&*slot.take().unwrap(), "hello");
+/// ```
+pub struct AtomicSlotUnsized<T: ?Sized> {
+    slot: AtomicSlot<Box<T>>,
+}
+
+/// An [`AtomicSlotUnsized`] holding trait objects, such as boxed closures in a command queue.
+///
+/// ```
+/// # use atomic_slot::AtomicDynSlot;
+/// let slot: AtomicDynSlot<dyn FnOnce() -> String + Send> = AtomicDynSlot::empty();
+/// let name = String::from("job");
+/// slot.store(Some(Box::new(move || name)));
+/// assert_eq!(slot.take().unwrap()(), "job");
+/// ```
+pub type AtomicDynSlot<T> = AtomicSlotUnsized<T>;
+
+impl<T: ?Sized> Default for AtomicSlotUnsized<T> {
+    /// Creates an empty `AtomicSlotUnsized<T>`.
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<T: ?Sized> AtomicSlotUnsized<T> {
+    /// Creates a new `AtomicSlotUnsized` containing `value`.
+    pub fn new(value: Box<T>) -> Self {
+        Self {
+            slot: AtomicSlot::new(Box::new(value)),
+        }
+    }
+
+    loom_const_fn! {
+        /// Creates an empty `AtomicSlotUnsized`.
+        pub const fn empty() -> Self {
+            Self {
+                slot: AtomicSlot::empty(),
+            }
+        }
+    }
+
+    /// Atomically swaps out the current contents for `value`, returning the old contents.
+    ///
+    /// Uses acquire–release ordering.
+    ///
+    /// ```
+    /// # use atomic_slot::AtomicSlotUnsized;
+    /// let slot = AtomicSlotUnsized::<[i32]>::new(Box::new([1, 2]));
+    /// let old = slot.swap(Some(Box::new([3])));
+    /// assert_eq!(*old.unwrap(), [1, 2]);
+    /// ```
+    pub fn swap(&self, value: Option<Box<T>>) -> Option<Box<T>> {
+        self.slot.swap(value.map(Box::new)).map(|outer| *outer)
+    }
+
+    /// Takes the current contents, leaving the slot empty.
+    ///
+    /// Uses acquire–release ordering.
+    pub fn take(&self) -> Option<Box<T>> {
+        self.swap(None)
+    }
+
+    /// Stores `value` into the slot, dropping whatever was there before.
+    ///
+    /// Uses acquire–release ordering.
+    pub fn store(&self, value: Option<Box<T>>) {
+        let _ = self.swap(value);
+    }
+
+    /// Returns `true` if the slot currently contains a value. Uses acquire ordering.
+    pub fn is_some(&self) -> bool {
+        self.slot.is_some()
+    }
+
+    /// Returns `true` if the slot is empty. Uses acquire ordering.
+    pub fn is_none(&self) -> bool {
+        self.slot.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn slices_and_trait_objects() {
+        let slot = AtomicSlotUnsized::<[i32]>::empty();
+        slot.store(Some(vec![1, 2, 3].into_boxed_slice()));
+        assert_eq!(*slot.take().unwrap(), [1, 2, 3]);
+        assert!(slot.is_none());
+
+        let slot: AtomicSlotUnsized<dyn Fn() -> i32> = AtomicSlotUnsized::new(Box::new(|| 7));
+        let f = slot.swap(Some(Box::new(|| 8))).unwrap();
+        assert_eq!(f(), 7);
+        assert_eq!(slot.take().unwrap()(), 8);
+    }
+
+    #[test]
+    fn dyn_fn_once_commands_run_after_take() {
+        let slot: AtomicDynSlot<dyn FnOnce() -> Vec<i32> + Send> = AtomicDynSlot::empty();
+        let data = vec![1, 2];
+        slot.store(Some(Box::new(move || data)));
+        let command = slot.swap(Some(Box::new(Vec::new))).unwrap();
+        assert_eq!(command(), [1, 2]);
+        assert!(slot.take().unwrap()().is_empty());
+    }
+
+    #[test]
+    fn drop_frees_both_boxes() {
+        let tracker = Arc::new(());
+        let captured = tracker.clone();
+        let slot: AtomicSlotUnsized<dyn Fn() -> usize + Send> =
+            AtomicSlotUnsized::new(Box::new(move || Arc::strong_count(&captured)));
+        assert_eq!(Arc::strong_count(&tracker), 2);
+        drop(slot);
+        assert_eq!(Arc::strong_count(&tracker), 1);
+    }
+}
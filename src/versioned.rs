@@ -0,0 +1,128 @@
+//! A slot that numbers each value published into it.
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+
+use crate::RwSpinSlot;
+use crate::sync::atomic::{AtomicU64, Ordering};
+
+struct Node<T> {
+    version: u64,
+    value: Box<T>,
+}
+
+/// A slot for values such as configuration that readers poll for updates.
+///
+/// Every [`publish`](VersionedSlot::publish) assigns the next version, starting from 1; an
+/// empty slot is at version 0. Readers remember the last version they saw and call
+/// [`load_if_newer`](VersionedSlot::load_if_newer) to pick up changes without needing a waker.
+///
+/// The version and value are published together through a [`RwSpinSlot`], so a reader
+/// always sees the version that belongs to the value it reads, and concurrent publishers are
+/// numbered in the order their writes take effect. The counter is a `u64` that wraps after
+/// 2<sup>64</sup> publishes, which is unreachable in practice.
+///
+/// Readers that only want to know whether to re-read can poll
+/// [`current_epoch`](VersionedSlot::current_epoch), a single atomic load that does not touch
+/// the value. [`PublishSlot`](crate::PublishSlot) pairs such an epoch with a plain slot whose
+/// value is taken rather than read.
+pub struct VersionedSlot<T> {
+    inner: RwSpinSlot<Node<T>>,
+    epoch: AtomicU64,
+}
+
+impl<T> Default for VersionedSlot<T> {
+    /// Creates an empty `VersionedSlot<T>` at version 0.
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<T> VersionedSlot<T> {
+    /// Creates an empty `VersionedSlot` at version 0.
+    pub fn empty() -> Self {
+        Self {
+            inner: RwSpinSlot::empty(),
+            epoch: AtomicU64::new(0),
+        }
+    }
+
+    /// Replaces the value, returning the version assigned to it.
+    ///
+    /// ```
+    /// # use atomic_slot::VersionedSlot;
+    /// let config = VersionedSlot::empty();
+    /// assert_eq!(config.publish(Box::new("a")), 1);
+    /// assert_eq!(config.publish(Box::new("b")), 2);
+    /// ```
+    pub fn publish(&self, value: Box<T>) -> u64 {
+        let mut version = 0;
+        self.inner.write_with(|current| {
+            version = current.map_or(0, |node| node.version).wrapping_add(1);
+            Box::new(Node { version, value })
+        });
+        // Bumped only once the value is installed, so an epoch implies its value
+        self.epoch.fetch_max(version, Ordering::Release);
+        version
+    }
+
+    /// Returns the version of the latest completed publish, without reading the value.
+    ///
+    /// The epoch is raised with release ordering after the value is installed and loaded with
+    /// acquire ordering here, so a reader that sees epoch `n` and then calls
+    /// [`load_if_newer`](Self::load_if_newer) observes version `n` or later. While a publish is
+    /// in progress the epoch may briefly trail [`load_version`](Self::load_version).
+    ///
+    /// ```
+    /// # use atomic_slot::VersionedSlot;
+    /// let config = VersionedSlot::empty();
+    /// assert_eq!(config.current_epoch(), 0);
+    /// config.publish(Box::new("v1"));
+    /// assert_eq!(config.current_epoch(), 1);
+    /// ```
+    pub fn current_epoch(&self) -> u64 {
+        self.epoch.load(Ordering::Acquire)
+    }
+
+    /// Returns the version of the current value, or 0 if nothing was published.
+    pub fn load_version(&self) -> u64 {
+        self.inner.read(|node| node.version).unwrap_or(0)
+    }
+
+    /// Runs `f` on the current value if its version is newer than `since`, returning the
+    /// version along with the result.
+    ///
+    /// ```
+    /// # use atomic_slot::VersionedSlot;
+    /// let config = VersionedSlot::empty();
+    /// config.publish(Box::new(8080));
+    /// let (seen, port) = config.load_if_newer(0, |port| *port).unwrap();
+    /// assert_eq!((seen, port), (1, 8080));
+    /// assert!(config.load_if_newer(seen, |port| *port).is_none());
+    /// ```
+    pub fn load_if_newer<R>(&self, since: u64, f: impl FnOnce(&T) -> R) -> Option<(u64, R)> {
+        self.inner
+            .read(|node| (node.version > since).then(|| (node.version, f(&node.value))))
+            .flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_track_publishes() {
+        let slot = VersionedSlot::empty();
+        assert_eq!(slot.load_version(), 0);
+        assert!(slot.load_if_newer(0, |v: &u8| *v).is_none());
+
+        for i in 1..=3 {
+            assert_eq!(slot.publish(Box::new(i)), u64::from(i));
+        }
+        assert_eq!(slot.load_version(), 3);
+        assert_eq!(slot.current_epoch(), 3);
+        assert_eq!(slot.load_if_newer(1, |v| *v), Some((3, 3)));
+        assert_eq!(slot.load_if_newer(3, |v| *v), None);
+    }
+}
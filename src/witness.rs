@@ -0,0 +1,201 @@
+//! A slot whose mutations can be ordered after the fact, for linearizability checks.
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+
+use crate::AtomicSlot;
+use crate::sync::atomic::{AtomicU64, Ordering};
+use crate::sync::spin_loop;
+
+/// The position of an operation in the history of a [`WitnessSlot`].
+///
+/// Witnesses from the same slot compare in the order the operations took effect.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Witness(u64);
+
+/// A slot like [`AtomicSlot`] that numbers every mutating operation, so test harnesses can
+/// reconstruct the order in which concurrent operations took effect.
+///
+/// A plain slot is a single word with no room for a counter, and bumping a separate counter
+/// after the swap could number two racing swaps out of order. `WitnessSlot` therefore holds
+/// the counter odd for the duration of each swap, like a seqlock, which serializes mutations
+/// on the slot. That makes it a testing aid rather than a drop-in replacement for
+/// `AtomicSlot` in production code.
+pub struct WitnessSlot<T> {
+    slot: AtomicSlot<T>,
+    ops: AtomicU64,
+}
+
+impl<T> Default for WitnessSlot<T> {
+    /// Creates an empty `WitnessSlot<T>`.
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<T> WitnessSlot<T> {
+    loom_const_fn! {
+        /// Creates an empty `WitnessSlot`.
+        pub const fn empty() -> Self {
+            Self {
+                slot: AtomicSlot::empty(),
+                ops: AtomicU64::new(0),
+            }
+        }
+    }
+
+    /// Atomically swaps out the current contents for `value`, returning the old contents and
+    /// a witness of the operation's position.
+    ///
+    /// `order` applies to the swap itself, as in [`AtomicSlot::swap_ordered`].
+    ///
+    /// ```
+    /// # use atomic_slot::WitnessSlot;
+    /// # use std::sync::atomic::Ordering;
+    /// let slot = WitnessSlot::empty();
+    /// let (_, first) = slot.swap_with_witness(Some(Box::new(1)), Ordering::AcqRel);
+    /// let (prev, second) = slot.swap_with_witness(None, Ordering::AcqRel);
+    /// assert_eq!(*prev.unwrap(), 1);
+    /// assert!(first < second);
+    /// ```
+    pub fn swap_with_witness(
+        &self,
+        value: Option<Box<T>>,
+        order: Ordering,
+    ) -> (Option<Box<T>>, Witness) {
+        let mut ops = self.ops.load(Ordering::Relaxed);
+        loop {
+            if ops & 1 == 1 {
+                spin_loop();
+                ops = self.ops.load(Ordering::Relaxed);
+                continue;
+            }
+            match self
+                .ops
+                .compare_exchange_weak(ops, ops + 1, Ordering::Acquire, Ordering::Relaxed)
+            {
+                Ok(_) => break,
+                Err(actual) => ops = actual,
+            }
+        }
+        let unlock = Unlock {
+            ops: &self.ops,
+            next: ops + 2,
+        };
+        let prev = self.slot.swap_ordered(value, order);
+        drop(unlock);
+        (prev, Witness(ops / 2 + 1))
+    }
+
+    /// Takes the current contents, leaving the slot empty.
+    ///
+    /// Taking is a mutation like any other, so it goes through
+    /// [`swap_with_witness`](Self::swap_with_witness) and uses up a witness; only the witness
+    /// itself is discarded.
+    ///
+    /// ```
+    /// # use atomic_slot::WitnessSlot;
+    /// # use std::sync::atomic::Ordering;
+    /// let slot = WitnessSlot::empty();
+    /// let (_, stored) = slot.swap_with_witness(Some(Box::new(1)), Ordering::AcqRel);
+    /// assert_eq!(*slot.take().unwrap(), 1);
+    /// let (_, emptied) = slot.swap_with_witness(None, Ordering::AcqRel);
+    /// assert!(emptied > stored);
+    /// ```
+    pub fn take(&self) -> Option<Box<T>> {
+        self.swap_with_witness(None, Ordering::AcqRel).0
+    }
+
+    /// Returns `true` if the slot currently contains a value.
+    pub fn is_some(&self) -> bool {
+        self.slot.is_some()
+    }
+}
+
+/// Makes the counter even again once a swap is done, including while unwinding from a panic.
+struct Unlock<'a> {
+    ops: &'a AtomicU64,
+    next: u64,
+}
+
+impl Drop for Unlock<'_> {
+    fn drop(&mut self) {
+        self.ops.store(self.next, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn witnesses_strictly_increase() {
+        let slot = WitnessSlot::empty();
+        let witnesses: Vec<_> = (0..5)
+            .map(|i| {
+                slot.swap_with_witness(Some(Box::new(i)), Ordering::AcqRel)
+                    .1
+            })
+            .collect();
+        assert!(witnesses.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn take_is_counted() {
+        let slot = WitnessSlot::empty();
+        let (_, before) = slot.swap_with_witness(Some(Box::new(1)), Ordering::AcqRel);
+        assert_eq!(*slot.take().unwrap(), 1);
+        let (_, after) = slot.swap_with_witness(None, Ordering::AcqRel);
+        assert_eq!(after.0, before.0 + 2);
+    }
+
+    // The publishing check that panics here only exists with debug assertions
+    #[cfg(debug_assertions)]
+    #[test]
+    fn panicking_swap_releases_the_counter() {
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+
+        let slot = WitnessSlot::empty();
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            slot.swap_with_witness(Some(Box::new(1)), Ordering::Relaxed);
+        }));
+        assert!(result.is_err());
+
+        // Would spin forever if the counter had been left odd
+        let (_, witness) = slot.swap_with_witness(Some(Box::new(2)), Ordering::AcqRel);
+        assert_eq!(witness, Witness(2));
+        assert_eq!(*slot.take().unwrap(), 2);
+    }
+
+    #[test]
+    fn witnesses_order_racing_swaps() {
+        let slot = Arc::new(WitnessSlot::empty());
+        let threads: Vec<_> = (0..4u32)
+            .map(|t| {
+                let slot = slot.clone();
+                thread::spawn(move || {
+                    (0..100)
+                        .map(|i| {
+                            let value = Box::new((t, i));
+                            let (prev, w) = slot.swap_with_witness(Some(value), Ordering::AcqRel);
+                            (w, prev.map(|p| *p), (t, i))
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        let mut history: Vec<_> = threads
+            .into_iter()
+            .flat_map(|th| th.join().unwrap())
+            .collect();
+        history.sort_by_key(|&(w, ..)| w);
+        // Each swap replaced exactly what the previous witness stored
+        assert_eq!(history[0].1, None);
+        for pair in history.windows(2) {
+            assert_eq!(pair[1].1, Some(pair[0].2));
+        }
+    }
+}
@@ -0,0 +1,39 @@
+//! Builds the crate without `std`, checking that the alloc-only configuration compiles and
+//! that leaving out `alloc` is reported clearly.
+
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn build(features: &str) -> Output {
+    let manifest_dir = env!("CARGO_MANIFEST_DIR");
+    let mut cmd = Command::new(env!("CARGO"));
+    cmd.args(["build", "--lib", "--no-default-features", "--manifest-path"])
+        .arg(Path::new(manifest_dir).join("Cargo.toml"))
+        // A separate target directory, as the outer `cargo test` holds the lock on its own
+        .arg("--target-dir")
+        .arg(Path::new(manifest_dir).join("target").join("alloc-only"));
+    if !features.is_empty() {
+        cmd.args(["--features", features]);
+    }
+    cmd.output().expect("failed to run cargo")
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn alloc_without_std_builds() {
+    let output = build("alloc");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn missing_alloc_is_a_compile_error() {
+    let output = build("");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("needs the `alloc` feature"), "{stderr}");
+}
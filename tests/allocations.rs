@@ -0,0 +1,84 @@
+//! Tests counting heap allocations, in their own binary so the counting allocator does not
+//! affect other tests.
+
+use atomic_slot::{AtomicSlot, Recycler, SmartSlot};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// Allocations made by the current thread while running `f`.
+fn allocations_during(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn warm_recycler_does_not_allocate() {
+    let recycler = Recycler::with_capacity(2);
+    let slot = AtomicSlot::empty();
+
+    // Warm up: one box in the slot and one spare
+    for i in 0..2u64 {
+        if let Some(old) = slot.swap_recycled(i, &recycler) {
+            recycler.recycle(old);
+        }
+    }
+
+    let allocations = allocations_during(|| {
+        for i in 2..1000u64 {
+            let old = slot.swap_recycled(i, &recycler).unwrap();
+            assert_eq!(*old, i - 1);
+            recycler.recycle(old);
+        }
+    });
+    assert_eq!(allocations, 0);
+    assert_eq!(*slot.take().unwrap(), 999);
+}
+
+#[test]
+fn inline_smart_slot_does_not_allocate() {
+    let slot = SmartSlot::empty();
+    let allocations = allocations_during(|| {
+        for i in 0..1000u32 {
+            slot.store(Some(i));
+            assert_eq!(slot.take(), Some(i));
+        }
+        assert_eq!(slot.swap(Some(7)), None);
+    });
+    assert_eq!(allocations, 0);
+    assert_eq!(slot.take(), Some(7));
+}
+
+#[test]
+fn zero_sized_slot_does_not_allocate() {
+    let slot = AtomicSlot::<()>::empty();
+    let allocations = allocations_during(|| {
+        for _ in 0..1000 {
+            slot.store(Some(Box::new(())));
+            assert!(slot.take().is_some());
+        }
+        assert!(slot.swap(Some(Box::new(()))).is_none());
+    });
+    assert_eq!(allocations, 0);
+    assert!(slot.is_some());
+}
@@ -67,3 +67,374 @@ fn concurrent_swaps() {
         assert_eq!(&seen, &[0, 1, 2]);
     });
 }
+
+#[test]
+fn move_if_empty_outcomes() {
+    use atomic_slot::{MoveOutcome, move_if_empty};
+
+    loom::model(|| {
+        let src = Arc::new(AtomicSlot::new(Box::new(1)));
+        let dst = Arc::new(AtomicSlot::empty());
+
+        let mover = {
+            let (src, dst) = (src.clone(), dst.clone());
+            loom::thread::spawn(move || move_if_empty(&src, &dst))
+        };
+        // Fills the destination, then the source while the value may be in flight
+        let rival = {
+            let (src, dst) = (src.clone(), dst.clone());
+            loom::thread::spawn(move || {
+                let rejected = [dst.try_store(Box::new(2)), src.try_store(Box::new(3))];
+                rejected
+                    .into_iter()
+                    .filter_map(Result::err)
+                    .map(|v| *v)
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        let outcome = mover.join().unwrap();
+        let mut values = rival.join().unwrap();
+        let (src, dst) = (src.take().map(|v| *v), dst.take().map(|v| *v));
+        match outcome {
+            MoveOutcome::Moved => assert_eq!(dst, Some(1)),
+            MoveOutcome::DestOccupied => assert_eq!((src, dst), (Some(1), Some(2))),
+            MoveOutcome::Returned(value) => {
+                assert_eq!((src, dst), (Some(3), Some(2)));
+                values.push(*value);
+            }
+            MoveOutcome::SourceEmpty => unreachable!("only the mover takes from the source"),
+        }
+
+        // No value is lost or duplicated, whichever way the race went
+        values.extend(src.into_iter().chain(dst));
+        values.sort();
+        assert_eq!(values, [1, 2, 3]);
+    });
+}
+
+#[cfg(feature = "spin-read")]
+#[test]
+fn concurrent_read_locked() {
+    use atomic_slot::SpinReadSlot;
+
+    loom::model(|| {
+        let slot = Arc::new(SpinReadSlot::new(Box::new(9)));
+
+        let readers: Vec<_> = (0..2)
+            .map(|_| {
+                let s = slot.clone();
+                loom::thread::spawn(move || s.read_locked(|v| *v))
+            })
+            .collect();
+
+        for r in readers {
+            // Every reader observes the value, never an emptied slot
+            assert_eq!(r.join().unwrap(), Some(9));
+        }
+        assert_eq!(*slot.take().unwrap(), 9);
+    });
+}
+
+#[test]
+fn racing_try_claim() {
+    loom::model(|| {
+        let flag = Arc::new(AtomicSlot::<()>::empty());
+
+        let threads: Vec<_> = (0..3)
+            .map(|_| {
+                let flag = flag.clone();
+                loom::thread::spawn(move || flag.try_claim())
+            })
+            .collect();
+
+        let claimed = threads
+            .into_iter()
+            .map(|t| t.join().unwrap())
+            .filter(|&c| c)
+            .count();
+        assert_eq!(claimed, 1);
+        assert!(flag.is_some());
+    });
+}
+
+#[test]
+fn rw_spin_readers_never_see_torn_or_freed_values() {
+    use atomic_slot::RwSpinSlot;
+    use loom::sync::atomic::{AtomicBool, Ordering};
+
+    // Flags its own drop, so a reader can tell whether the pair it holds was freed under it
+    struct Pair(usize, usize, Arc<AtomicBool>);
+    impl Drop for Pair {
+        fn drop(&mut self) {
+            self.2.store(true, Ordering::Release);
+        }
+    }
+
+    // A single reader: with two readers spinning against a writer the model does not
+    // terminate within loom's branch limit. The reader is spawned so loom also schedules it
+    // after the write
+    loom::model(|| {
+        let freed = Arc::new(AtomicBool::new(false));
+        let slot = Arc::new(RwSpinSlot::new(Box::new(Pair(0, 0, freed.clone()))));
+
+        let reader = {
+            let slot = slot.clone();
+            loom::thread::spawn(move || {
+                let (a, b) = slot
+                    .read(|pair| {
+                        let seen = (pair.0, pair.1);
+                        // Lets the writer run while the reader still holds the pair
+                        loom::thread::yield_now();
+                        if seen == (0, 0) {
+                            assert!(!freed.load(Ordering::Acquire));
+                        }
+                        seen
+                    })
+                    .unwrap();
+                assert_eq!(a, b);
+            })
+        };
+        slot.write(Box::new(Pair(1, 1, Arc::new(AtomicBool::new(false)))));
+
+        reader.join().unwrap();
+        assert_eq!(slot.read(|pair| (pair.0, pair.1)), Some((1, 1)));
+    });
+}
+
+#[test]
+fn racing_reservers() {
+    use atomic_slot::ReserveSlot;
+
+    loom::model(|| {
+        let slot = Arc::new(ReserveSlot::empty());
+
+        let threads: Vec<_> = (0..2)
+            .map(|n| {
+                let s = slot.clone();
+                loom::thread::spawn(move || {
+                    let reservation = s.reserve()?;
+                    reservation.fulfill(Box::new(n));
+                    Some(n)
+                })
+            })
+            .collect();
+
+        let winners: Vec<_> = threads
+            .into_iter()
+            .filter_map(|th| th.join().unwrap())
+            .collect();
+        // A fulfilled slot can't be reserved again, so exactly one producer wins and its value
+        // is the one left in the slot
+        assert_eq!(winners.len(), 1);
+        assert_eq!(slot.take().map(|v| *v), Some(winners[0]));
+    });
+}
+
+#[test]
+fn versioned_publishers_get_distinct_versions() {
+    use atomic_slot::VersionedSlot;
+
+    loom::model(|| {
+        let slot = Arc::new(VersionedSlot::empty());
+
+        let publishers: Vec<_> = (0..2)
+            .map(|n| {
+                let slot = slot.clone();
+                loom::thread::spawn(move || {
+                    let first = slot.publish(Box::new(n));
+                    let second = slot.publish(Box::new(n));
+                    assert!(second > first);
+                    [first, second]
+                })
+            })
+            .collect();
+
+        let mut versions: Vec<_> = publishers
+            .into_iter()
+            .flat_map(|th| th.join().unwrap())
+            .collect();
+        versions.sort();
+        assert_eq!(versions, [1, 2, 3, 4]);
+        assert_eq!(slot.load_version(), 4);
+    });
+}
+
+#[test]
+fn tag_transitions_survive_value_swaps() {
+    use atomic_slot::TaggedSlot;
+
+    loom::model(|| {
+        let slot = Arc::new(TaggedSlot::new(Box::new(1u32)));
+
+        let tagger = {
+            let slot = slot.clone();
+            loom::thread::spawn(move || {
+                assert_eq!(slot.compare_exchange_tag(0, 1), Ok(0));
+                assert_eq!(slot.compare_exchange_tag(1, 2), Ok(1));
+            })
+        };
+        let swapper = {
+            let slot = slot.clone();
+            loom::thread::spawn(move || {
+                let first = *slot.swap(Some(Box::new(2))).unwrap();
+                let second = *slot.swap(Some(Box::new(3))).unwrap();
+                (first, second)
+            })
+        };
+
+        tagger.join().unwrap();
+        // Neither swap loses the tag, and neither retag loses a value
+        assert_eq!(swapper.join().unwrap(), (1, 2));
+        assert_eq!(slot.tag(), 2);
+        assert_eq!(*slot.take().unwrap(), 3);
+    });
+}
+
+#[test]
+fn concurrent_merges_sum() {
+    loom::model(|| {
+        let slot = Arc::new(AtomicSlot::new(Box::new(0)));
+        let add = |acc: Option<Box<i32>>, mut n: Box<i32>| {
+            *n += acc.map_or(0, |acc| *acc);
+            n
+        };
+
+        let threads: Vec<_> = [1, 2]
+            .into_iter()
+            .map(|n| {
+                let slot = slot.clone();
+                loom::thread::spawn(move || slot.merge(Box::new(n), add))
+            })
+            .collect();
+        for th in threads {
+            th.join().unwrap();
+        }
+        assert_eq!(*slot.take().unwrap(), 3);
+    });
+}
+
+#[test]
+fn new_epoch_implies_new_value() {
+    use atomic_slot::VersionedSlot;
+
+    loom::model(|| {
+        let slot = Arc::new(VersionedSlot::empty());
+        slot.publish(Box::new(1));
+
+        // The reader runs on a spawned thread; loom does not reorder the spawning thread's first
+        // load before the spawned thread's writes, so a reader on the main thread would only
+        // ever see the old epoch.
+        let reader = {
+            let slot = slot.clone();
+            loom::thread::spawn(move || {
+                if slot.current_epoch() == 2 {
+                    assert_eq!(slot.load_if_newer(1, |v| *v), Some((2, 2)));
+                }
+            })
+        };
+
+        slot.publish(Box::new(2));
+        reader.join().unwrap();
+        assert_eq!(slot.current_epoch(), 2);
+    });
+}
+
+#[test]
+fn publish_epoch_implies_published_value() {
+    use atomic_slot::PublishSlot;
+
+    loom::model(|| {
+        let slot = Arc::new(PublishSlot::empty());
+
+        let publisher = {
+            let slot = slot.clone();
+            loom::thread::spawn(move || {
+                slot.publish(Box::new(1));
+            })
+        };
+        let reader = {
+            let slot = slot.clone();
+            loom::thread::spawn(move || {
+                if slot.current_epoch() == 1 {
+                    // A plain load, unlike the swap in `take`, could still see the empty slot
+                    // without the epoch's ordering
+                    assert!(slot.is_some());
+                    assert_eq!(slot.take().map(|v| *v), Some(1));
+                }
+            })
+        };
+
+        publisher.join().unwrap();
+        reader.join().unwrap();
+        assert_eq!(slot.current_epoch(), 1);
+    });
+}
+
+#[test]
+fn weak_exchange_retries_until_stored() {
+    use std::sync::atomic::Ordering::{AcqRel, Acquire};
+
+    loom::model(|| {
+        let slot = Arc::new(AtomicSlot::empty());
+
+        let producers: Vec<_> = (0..2)
+            .map(|n| {
+                let slot = slot.clone();
+                loom::thread::spawn(move || {
+                    let mut value = Some(Box::new(n));
+                    // Fails while the other producer's value is still in the slot, and may
+                    // also fail spuriously
+                    while let Err(back) = slot.compare_exchange_weak(None, value, AcqRel, Acquire) {
+                        value = back;
+                        loom::thread::yield_now();
+                    }
+                })
+            })
+            .collect();
+
+        let mut seen = Vec::new();
+        while seen.len() < 2 {
+            match slot.take() {
+                Some(v) => seen.push(*v),
+                None => loom::thread::yield_now(),
+            }
+        }
+        for producer in producers {
+            producer.join().unwrap();
+        }
+        seen.sort();
+        assert_eq!(seen, [0, 1]);
+        assert!(slot.is_none());
+    });
+}
+
+#[test]
+fn fill_from_iter_returns_an_item_it_could_not_store() {
+    use atomic_slot::fill_from_iter;
+
+    loom::model(|| {
+        let slots = Arc::new([AtomicSlot::empty()]);
+
+        let filler = {
+            let slots = slots.clone();
+            loom::thread::spawn(move || {
+                let mut items = core::iter::once(Box::new(1));
+                let (stored, pending) = fill_from_iter(&slots[..], &mut items);
+                let leftover = pending.or_else(|| items.next()).map(|item| *item);
+                (stored, leftover)
+            })
+        };
+        let rival = {
+            let slots = slots.clone();
+            loom::thread::spawn(move || slots[0].try_store(Box::new(2)).is_ok())
+        };
+
+        let (stored, leftover) = filler.join().unwrap();
+        let rival_stored = rival.join().unwrap();
+        // Exactly one item ends up in the slot, and an item that lost the race comes back
+        assert_eq!(stored + usize::from(rival_stored), 1);
+        assert_eq!(leftover, (stored == 0).then_some(1));
+        assert_eq!(*slots[0].take().unwrap(), if rival_stored { 2 } else { 1 });
+    });
+}
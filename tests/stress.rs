@@ -0,0 +1,112 @@
+//! Long-running stress tests, ignored by default. Run them with
+//! `cargo test --release --test stress -- --ignored`.
+//!
+//! Thread and iteration counts are read from `STRESS_PRODUCERS`, `STRESS_CONSUMERS` and
+//! `STRESS_ITERATIONS`.
+
+use atomic_slot::AtomicSlot;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering};
+use std::thread;
+
+fn env_or(name: &str, default: usize) -> usize {
+    std::env::var(name)
+        .ok()
+        .map(|v| {
+            v.parse()
+                .unwrap_or_else(|_| panic!("{name} must be a number, got {v:?}"))
+        })
+        .unwrap_or(default)
+}
+
+/// A box with a unique id that counts its live instances, so lost boxes show up as leaks and
+/// double frees as a negative balance.
+struct Tracked {
+    id: usize,
+    live: Arc<AtomicUsize>,
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        self.live.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Every box produced is received exactly once, by a swap, a take or the final drain.
+#[test]
+#[ignore]
+fn boxes_are_conserved() {
+    let producers = env_or("STRESS_PRODUCERS", 4);
+    let consumers = env_or("STRESS_CONSUMERS", 4);
+    let iterations = env_or("STRESS_ITERATIONS", 100_000);
+
+    let slot = Arc::new(AtomicSlot::<Tracked>::empty());
+    let seen: Arc<Vec<AtomicU8>> = Arc::new(
+        (0..producers * iterations)
+            .map(|_| AtomicU8::new(0))
+            .collect(),
+    );
+    let live = Arc::new(AtomicUsize::new(0));
+    let done = Arc::new(AtomicBool::new(false));
+
+    let receive = {
+        let seen = seen.clone();
+        move |tracked: Box<Tracked>| {
+            let prev = seen[tracked.id].fetch_add(1, Ordering::Relaxed);
+            assert_eq!(prev, 0, "box {} received twice", tracked.id);
+        }
+    };
+
+    let producer_threads: Vec<_> = (0..producers)
+        .map(|p| {
+            let (slot, live, receive) = (slot.clone(), live.clone(), receive.clone());
+            thread::spawn(move || {
+                for i in 0..iterations {
+                    live.fetch_add(1, Ordering::Relaxed);
+                    let tracked = Box::new(Tracked {
+                        id: p * iterations + i,
+                        live: live.clone(),
+                    });
+                    if let Some(old) = slot.swap(tracked) {
+                        receive(old);
+                    }
+                }
+            })
+        })
+        .collect();
+    let consumer_threads: Vec<_> = (0..consumers)
+        .map(|_| {
+            let (slot, done, receive) = (slot.clone(), done.clone(), receive.clone());
+            thread::spawn(move || {
+                while !done.load(Ordering::Acquire) {
+                    match slot.take() {
+                        Some(tracked) => receive(tracked),
+                        None => thread::yield_now(),
+                    }
+                }
+            })
+        })
+        .collect();
+
+    for th in producer_threads {
+        th.join().unwrap();
+    }
+    done.store(true, Ordering::Release);
+    for th in consumer_threads {
+        th.join().unwrap();
+    }
+    if let Some(tracked) = slot.take() {
+        receive(tracked);
+    }
+
+    let missing: Vec<_> = (0..seen.len())
+        .filter(|&id| seen[id].load(Ordering::Relaxed) == 0)
+        .collect();
+    assert!(
+        missing.is_empty(),
+        "{} boxes lost, first {:?}",
+        missing.len(),
+        &missing[..missing.len().min(8)]
+    );
+    assert_eq!(live.load(Ordering::Relaxed), 0);
+}